@@ -1,6 +1,7 @@
 use std::{str::FromStr, string::ParseError};
 
 use anyhow::{Context, Result};
+use aoc_lib::number_theory::crt;
 use itertools::Itertools;
 use utils::read_file;
 
@@ -32,20 +33,15 @@ fn part1(world: &World) -> Option<u64> {
     result.map(|(bus_id, num_of_min)| bus_id * num_of_min)
 }
 
-fn part2(world: &World) -> u64 {
-    world
+fn part2(world: &World) -> Option<u64> {
+    // Bus m leaving `diff` minutes after t means t = -diff (mod m).
+    let congruences: Vec<(i64, i64)> = world
         .bus_schedule
         .iter()
         .enumerate()
-        .filter_map(|(diff, modulo)| modulo.map(|m| (diff as u64, m)))
-        // https://en.wikipedia.org/wiki/Chinese_remainder_theorem#Search_by_sieving
-        .fold((1, 0), |(step, mut ans), (diff, modulo)| {
-            while (ans + diff) % modulo != 0 {
-                ans += step;
-            }
-            (step * modulo, ans)
-        })
-        .1
+        .filter_map(|(diff, modulo)| modulo.map(|m| (-(diff as i64), m as i64)))
+        .collect();
+    crt(&congruences).map(|(t, _)| t as u64)
 }
 
 fn main() -> Result<()> {
@@ -53,7 +49,7 @@ fn main() -> Result<()> {
     let result = part1(&world).context("Couldn't find an answer for part 1")?;
     println!("part 1: {}", result);
 
-    let result = part2(&world);
+    let result = part2(&world).context("Couldn't find an answer for part 2")?;
     println!("part 2: {}", result);
     Ok(())
 }
@@ -65,11 +61,11 @@ mod tests {
     fn test_part2() -> Result<()> {
         let world = "0\n67,7,59,61".parse()?;
         let result = part2(&world);
-        assert_eq!(result, 754018);
+        assert_eq!(result, Some(754018));
 
         let world = "0\n67,7,x,59,61".parse()?;
         let result = part2(&world);
-        assert_eq!(result, 1261476);
+        assert_eq!(result, Some(1261476));
         Ok(())
     }
 }
@@ -1,7 +1,7 @@
-use std::{collections::HashMap, iter, str::FromStr, string::ParseError};
+use std::{str::FromStr, string::ParseError};
 
 use anyhow::Result;
-use itertools::Itertools;
+use aoc_lib::automaton::{count, Automaton, Neighbourhood, SparseAutomaton};
 use utils::read_file;
 
 #[derive(parse_display::FromStr, PartialEq, Debug, Clone, Copy)]
@@ -12,73 +12,23 @@ enum State {
     Inactive,
 }
 
-type Point = Vec<isize>;
-
 struct PocketDimension<const N: usize> {
-    state: HashMap<Point, State>,
+    state: SparseAutomaton<State, N>,
 }
 
 impl<const N: usize> PocketDimension<N> {
     fn tick(&mut self) {
-        self.state = self
-            .state
-            .keys()
-            .flat_map(|p| self.get_neighborhood(p))
-            .unique()
-            .map(|p| {
-                let new = self.new_state_for_point(&p);
-                (p, new)
-            })
-            .collect();
-    }
-
-    #[allow(clippy::ptr_arg)]
-    fn state_for_point(&self, point: &Vec<isize>) -> &State {
-        self.state.get(point).unwrap_or(&State::Inactive)
-    }
-
-    #[allow(clippy::ptr_arg)]
-    fn new_state_for_point(&self, point: &Vec<isize>) -> State {
-        let neighborhood = self.get_neighborhood(point);
-        let active_neighbor_count = neighborhood
-            .iter()
-            .filter(|&p| p != point)
-            .filter(|p| self.state_for_point(p) == &State::Active)
-            .count();
-        match (self.state_for_point(point), active_neighbor_count) {
-            (State::Active, 2..=3) => State::Active,
-            (State::Inactive, 3) => State::Active,
-            _ => State::Inactive,
-        }
+        self.state.step(
+            |state, neighbors| match (state, count(neighbors, &State::Active)) {
+                (State::Active, 2..=3) => State::Active,
+                (State::Inactive, 3) => State::Active,
+                _ => State::Inactive,
+            },
+        );
     }
 
     fn count_active(&self) -> usize {
-        self.state.values().filter(|&s| s == &State::Active).count()
-    }
-
-    // this will include the point passed in
-    fn get_neighborhood(&self, point: &[isize]) -> Vec<Point> {
-        (0..N)
-            .map(|n| (point[n] - 1..=point[n] + 1))
-            .fold(Vec::new(), |acc, r| {
-                let mut new_acc = vec![];
-                let is_empty = acc.is_empty();
-                for i in r {
-                    if is_empty {
-                        new_acc.push(vec![i]);
-                    } else {
-                        for point in &acc {
-                            let new_point = point
-                                .iter()
-                                .copied()
-                                .chain(iter::once(i))
-                                .collect::<Vec<isize>>();
-                            new_acc.push(new_point);
-                        }
-                    }
-                }
-                new_acc
-            })
+        self.state.count(&State::Active)
     }
 }
 
@@ -86,23 +36,20 @@ impl<const N: usize> FromStr for PocketDimension<N> {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let state = s
-            .split_whitespace()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.chars()
-                    .map(|c| c.to_string())
-                    .filter_map(|c| c.parse::<State>().ok())
-                    .enumerate()
-                    .map(|(x, s)| {
-                        let mut base_vec = vec![0; N];
-                        base_vec[0] = x as isize;
-                        base_vec[1] = y as isize;
-                        (base_vec, s)
-                    })
-                    .collect::<Vec<_>>() // need to collect so y can keep living
-            })
-            .collect();
+        let cells = s.split_whitespace().enumerate().flat_map(|(y, line)| {
+            line.chars()
+                .map(|c| c.to_string())
+                .filter_map(|c| c.parse::<State>().ok())
+                .enumerate()
+                .map(|(x, s)| {
+                    let mut point = [0; N];
+                    point[0] = x as i64;
+                    point[1] = y as i64;
+                    (point, s)
+                })
+                .collect::<Vec<_>>() // need to collect so y can keep living
+        });
+        let state = SparseAutomaton::new(Neighbourhood::moore(), State::Inactive, cells);
         Ok(PocketDimension { state })
     }
 }
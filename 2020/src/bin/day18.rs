@@ -1,147 +1,294 @@
+use std::{collections::HashMap, fmt};
+
 use anyhow::Result;
-use nom::{
-    branch::alt, bytes::complete::take_while, character::complete::char, combinator::map,
-    combinator::map_res, sequence::delimited, sequence::pair, IResult,
-};
 use utils::read_lines;
 
 type Number = u64;
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    fn from_char(c: char) -> Option<Op> {
+        match c {
+            '+' => Some(Op::Add),
+            '-' => Some(Op::Sub),
+            '*' => Some(Op::Mul),
+            '/' => Some(Op::Div),
+            _ => None,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Op::Add => '+',
+            Op::Sub => '-',
+            Op::Mul => '*',
+            Op::Div => '/',
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 enum Expr {
     Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
     Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
     Number(Number),
 }
 
-fn is_char_digit(c: char) -> bool {
-    c.is_ascii_digit()
-}
+impl Expr {
+    fn binary(op: Op, lhs: Expr, rhs: Expr) -> Expr {
+        let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+        match op {
+            Op::Add => Expr::Add(lhs, rhs),
+            Op::Sub => Expr::Sub(lhs, rhs),
+            Op::Mul => Expr::Mul(lhs, rhs),
+            Op::Div => Expr::Div(lhs, rhs),
+        }
+    }
 
-fn parse_number(input: &str) -> IResult<&str, Number> {
-    map_res(take_while(is_char_digit), str::parse)(input)
+    fn as_binary(&self) -> Option<(Op, &Expr, &Expr)> {
+        match self {
+            Expr::Add(lhs, rhs) => Some((Op::Add, lhs, rhs)),
+            Expr::Sub(lhs, rhs) => Some((Op::Sub, lhs, rhs)),
+            Expr::Mul(lhs, rhs) => Some((Op::Mul, lhs, rhs)),
+            Expr::Div(lhs, rhs) => Some((Op::Div, lhs, rhs)),
+            Expr::Number(_) => None,
+        }
+    }
 }
 
-fn parse_parens(input: &str) -> IResult<&str, Expr> {
-    delimited(char('('), parse_expr, char(')'))(input)
+// Brackets every operation inside the outermost one, so it's clear how the
+// precedence table grouped things.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn operand(f: &mut fmt::Formatter<'_>, expr: &Expr) -> fmt::Result {
+            match expr {
+                Expr::Number(n) => write!(f, "{}", n),
+                _ => write!(f, "({})", expr),
+            }
+        }
+        match self.as_binary() {
+            Some((op, lhs, rhs)) => {
+                operand(f, lhs)?;
+                write!(f, " {} ", op.symbol())?;
+                operand(f, rhs)
+            }
+            None => operand(f, self),
+        }
+    }
 }
 
-fn parse_factor(input: &str) -> IResult<&str, Expr> {
-    alt((map(parse_number, Expr::Number), parse_parens))(input)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Assoc {
+    Left,
+    // Nothing in the puzzle is right-associative, but a table can ask for it.
+    #[allow(dead_code)]
+    Right,
 }
 
-fn parse_mul(input: &str) -> IResult<&str, Expr> {
-    let sub = pair(char('*'), parse_expr);
-    let (input, (lhs, (_, rhs))) = pair(parse_factor, sub)(input)?;
-    Ok((input, Expr::Mul(Box::new(lhs), Box::new(rhs))))
+// How tightly each operator binds. Higher binds tighter; operators missing
+// from the table are parse errors.
+#[derive(Debug, Clone)]
+struct Precedence {
+    table: HashMap<Op, (u8, Assoc)>,
 }
 
-fn parse_add(input: &str) -> IResult<&str, Expr> {
-    let sub = pair(char('+'), parse_expr);
-    let (input, (lhs, (_, rhs))) = pair(parse_factor, sub)(input)?;
-    Ok((input, Expr::Add(Box::new(lhs), Box::new(rhs))))
-}
+impl Precedence {
+    fn new(table: &[(Op, u8, Assoc)]) -> Self {
+        Precedence {
+            table: table
+                .iter()
+                .map(|&(op, level, assoc)| (op, (level, assoc)))
+                .collect(),
+        }
+    }
 
-fn parse_expr(input: &str) -> IResult<&str, Expr> {
-    alt((parse_add, parse_mul, parse_factor))(input)
-}
+    // Everything equal, so it all goes left to right.
+    fn flat() -> Self {
+        Self::new(&[
+            (Op::Add, 1, Assoc::Left),
+            (Op::Sub, 1, Assoc::Left),
+            (Op::Mul, 1, Assoc::Left),
+            (Op::Div, 1, Assoc::Left),
+        ])
+    }
 
-fn parse(input: &str) -> Result<Expr, String> {
-    let i = input.trim().replace(" ", "");
-    parse_expr(&i)
-        .map_err(|e| format!("{}", e))
-        .and_then(|(remaining, parsed)| {
-            if !remaining.is_empty() {
-                return Err(format!("Parsed: {:?}, Remaining, {}", parsed, remaining));
-            }
-            Ok(parsed)
-        })
-}
+    fn addition_first() -> Self {
+        Self::new(&[
+            (Op::Add, 2, Assoc::Left),
+            (Op::Sub, 2, Assoc::Left),
+            (Op::Mul, 1, Assoc::Left),
+            (Op::Div, 1, Assoc::Left),
+        ])
+    }
 
-fn parse_parens_adv(input: &str) -> IResult<&str, Expr> {
-    delimited(char('('), parse_expr_adv, char(')'))(input)
+    // The usual school rules.
+    #[allow(dead_code)]
+    fn standard() -> Self {
+        Self::new(&[
+            (Op::Add, 1, Assoc::Left),
+            (Op::Sub, 1, Assoc::Left),
+            (Op::Mul, 2, Assoc::Left),
+            (Op::Div, 2, Assoc::Left),
+        ])
+    }
 }
 
-fn parse_factor_adv(input: &str) -> IResult<&str, Expr> {
-    alt((map(parse_number, Expr::Number), parse_parens_adv))(input)
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Token {
+    Number(Number),
+    Op(Op),
+    Open,
+    Close,
 }
 
-fn parse_term(input: &str) -> IResult<&str, Expr> {
-    alt((parse_add_adv, parse_factor_adv))(input)
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let token = match c {
+            ' ' => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '0'..='9' => {
+                let mut end = i + 1;
+                while let Some((j, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = j + 1;
+                }
+                let n = input[i..end]
+                    .parse()
+                    .map_err(|e| format!("{} at {}", e, i))?;
+                Token::Number(n)
+            }
+            c => Token::Op(Op::from_char(c).ok_or(format!("unexpected {:?} at {}", c, i))?),
+        };
+        tokens.push((i, token));
+    }
+    Ok(tokens)
 }
 
-fn parse_mul_adv(input: &str) -> IResult<&str, Expr> {
-    let sub = pair(char('*'), parse_expr_adv);
-    let (input, (lhs, (_, rhs))) = pair(parse_term, sub)(input)?;
-    Ok((input, Expr::Mul(Box::new(lhs), Box::new(rhs))))
+// A Pratt parser: parse a operand, then keep folding in operators for as long
+// as they bind at least as tightly as `min_level`.
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    precedence: &'a Precedence,
+    end: usize,
 }
 
-fn parse_add_adv(input: &str) -> IResult<&str, Expr> {
-    let sub = pair(char('+'), parse_term);
-    let (input, (lhs, (_, rhs))) = pair(parse_factor_adv, sub)(input)?;
-    Ok((input, Expr::Add(Box::new(lhs), Box::new(rhs))))
-}
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).map(|&(_, t)| t)
+    }
 
-fn parse_expr_adv(input: &str) -> IResult<&str, Expr> {
-    alt((parse_mul_adv, parse_term))(input)
-}
+    fn offset(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end, |&(i, _)| i)
+    }
 
-fn parse_adv(input: &str) -> Result<Expr, String> {
-    let i = input.trim().replace(" ", "");
-    parse_expr_adv(&i)
-        .map_err(|e| format!("{}", e))
-        .and_then(|(remaining, parsed)| {
-            if !remaining.is_empty() {
-                return Err(format!("Parsed: {:?}, Remaining, {}", parsed, remaining));
-            }
-            Ok(parsed)
-        })
-}
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("unexpected {:?} at {}", token, self.offset()),
+            None => "unexpected end of input".to_string(),
+        }
+    }
 
-fn walk(ast: &Expr) -> Number {
-    match ast {
-        Expr::Add(lhs, rhs) => {
-            let lhs = walk(lhs);
-            let rhs = walk(rhs);
-            lhs + rhs
+    fn operand(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Number(n)) => {
+                self.position += 1;
+                Ok(Expr::Number(n))
+            }
+            Some(Token::Open) => {
+                self.position += 1;
+                let expr = self.expr(0)?;
+                if self.peek() != Some(Token::Close) {
+                    return Err(self.unexpected());
+                }
+                self.position += 1;
+                Ok(expr)
+            }
+            _ => Err(self.unexpected()),
         }
-        Expr::Mul(lhs, rhs) => {
-            let lhs = walk(lhs);
-            let rhs = walk(rhs);
-            lhs * rhs
+    }
+
+    fn expr(&mut self, min_level: u8) -> Result<Expr, String> {
+        let mut lhs = self.operand()?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let &(level, assoc) = self.precedence.table.get(&op).ok_or(format!(
+                "no precedence for {:?} at {}",
+                op,
+                self.offset()
+            ))?;
+            if level < min_level {
+                break;
+            }
+            self.position += 1;
+            let next_level = match assoc {
+                Assoc::Left => level + 1,
+                Assoc::Right => level,
+            };
+            let rhs = self.expr(next_level)?;
+            lhs = Expr::binary(op, lhs, rhs);
         }
-        Expr::Number(num) => *num,
+        Ok(lhs)
     }
 }
 
-fn calculate(equation: &str) -> Result<Number, String> {
-    // So I helped write a little calculator - https://github.com/brian-dawn/trusty-calculator
-    // and decided to use chunks of it to make my life easier.
-    // It wasn't working, but I realized it was evaluating right-to-left
-    // instead of left-to-right. I tried for a bit to get it to go the
-    // other way but it was much easier to flip the input.
-    let equation: String = equation
-        .chars()
-        .rev()
-        .map(|c| match c {
-            '(' => ')',
-            ')' => '(',
-            a => a,
-        })
-        .collect();
-    parse(&equation).map(|expr| walk(&expr))
+fn parse(input: &str, precedence: &Precedence) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+        precedence,
+        end: input.len(),
+    };
+    let expr = parser.expr(0)?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(expr)
 }
 
-fn calculate_adv(equation: &str) -> Result<Number, String> {
-    parse_adv(equation).map(|expr| walk(&expr))
+fn walk(ast: &Expr) -> Result<Number, String> {
+    if let Expr::Number(num) = ast {
+        return Ok(*num);
+    }
+    let (op, lhs, rhs) = ast.as_binary().unwrap();
+    let (lhs, rhs) = (walk(lhs)?, walk(rhs)?);
+    match op {
+        Op::Add => lhs.checked_add(rhs),
+        Op::Sub => lhs.checked_sub(rhs),
+        Op::Mul => lhs.checked_mul(rhs),
+        Op::Div => lhs.checked_div(rhs),
+    }
+    .ok_or(format!("{} {} {} is out of range", lhs, op.symbol(), rhs))
+}
+
+fn calculate(equation: &str, precedence: &Precedence) -> Result<Number, String> {
+    parse(equation, precedence).and_then(|expr| walk(&expr))
 }
 
 fn part1(equations: &[String]) -> Number {
-    equations.iter().filter_map(|s| calculate(s).ok()).sum()
+    let precedence = Precedence::flat();
+    equations
+        .iter()
+        .filter_map(|s| calculate(s, &precedence).ok())
+        .sum()
 }
 
 fn part2(equations: &[String]) -> Number {
-    equations.iter().filter_map(|s| calculate_adv(s).ok()).sum()
+    let precedence = Precedence::addition_first();
+    equations
+        .iter()
+        .filter_map(|s| calculate(s, &precedence).ok())
+        .sum()
 }
 
 fn main() -> Result<()> {
@@ -161,25 +308,72 @@ mod tests {
 
     #[test]
     fn test_calculate() -> Result<(), String> {
-        let result = calculate("(2 * 3) * (1 + 1)")?;
+        let flat = Precedence::flat();
+        let result = calculate("(2 * 3) * (1 + 1)", &flat)?;
         assert_eq!(result, 12);
-        let result = calculate("2 * 3 + (4 * 5)")?;
+        let result = calculate("2 * 3 + (4 * 5)", &flat)?;
         assert_eq!(result, 26);
-        let result = calculate("5 + (8 * 3 + 9 + 3 * 4 * 3)")?;
+        let result = calculate("5 + (8 * 3 + 9 + 3 * 4 * 3)", &flat)?;
         assert_eq!(result, 437);
         Ok(())
     }
 
     #[test]
     fn test_calculate_adv() -> Result<(), String> {
-        let result = calculate_adv("1 + (2 * 3) + (4 * (5 + 6))")?;
+        let addition_first = Precedence::addition_first();
+        let result = calculate("1 + (2 * 3) + (4 * (5 + 6))", &addition_first)?;
         assert_eq!(result, 51);
-        let result = calculate_adv("2 * 3 + (4 * 5)")?;
+        let result = calculate("2 * 3 + (4 * 5)", &addition_first)?;
         assert_eq!(result, 46);
-        let result = calculate_adv("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")?;
+        let result = calculate("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", &addition_first)?;
         assert_eq!(result, 669060);
-        let result = calculate_adv("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")?;
+        let result = calculate(
+            "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+            &addition_first,
+        )?;
         assert_eq!(result, 23340);
         Ok(())
     }
+
+    #[test]
+    fn test_precedence_tables() -> Result<(), String> {
+        let expr = "18 - 4 - 2 * 3 + 12 / 4";
+        let show = |precedence| parse(expr, &precedence).map(|e| e.to_string());
+        assert_eq!(show(Precedence::flat())?, "((((18 - 4) - 2) * 3) + 12) / 4");
+        assert_eq!(
+            show(Precedence::addition_first())?,
+            "(((18 - 4) - 2) * (3 + 12)) / 4"
+        );
+        assert_eq!(
+            show(Precedence::standard())?,
+            "((18 - 4) - (2 * 3)) + (12 / 4)"
+        );
+        assert_eq!(calculate(expr, &Precedence::flat())?, 12);
+        assert_eq!(calculate(expr, &Precedence::standard())?, 11);
+
+        let right = Precedence::new(&[(Op::Sub, 1, Assoc::Right)]);
+        assert_eq!(parse("8 - 4 - 2", &right)?.to_string(), "8 - (4 - 2)");
+        assert_eq!(calculate("8 - 4 - 2", &right)?, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let flat = Precedence::flat();
+        assert_eq!(
+            parse("2 * (3 + 4", &flat),
+            Err("unexpected end of input".to_string())
+        );
+        assert_eq!(
+            parse("2 * 3)", &flat),
+            Err("unexpected Close at 5".to_string())
+        );
+        assert_eq!(
+            parse("2 % 3", &flat),
+            Err("unexpected '%' at 2".to_string())
+        );
+        assert!(parse("2 + 3", &Precedence::new(&[(Op::Mul, 1, Assoc::Left)])).is_err());
+        assert!(calculate("1 - 2", &flat).is_err());
+        assert!(calculate("1 / 0", &flat).is_err());
+    }
 }
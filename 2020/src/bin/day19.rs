@@ -1,8 +1,11 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
 
 use anyhow::Result;
 use itertools::Itertools;
-use regex::Regex;
 use utils::{read_file, InputParseError};
 
 type RuleSet = HashMap<usize, Rule>;
@@ -12,36 +15,6 @@ enum Rule {
     Char(char),
     Matches(Vec<usize>),
     MatchesOneOf(Vec<Vec<usize>>),
-    None,
-}
-
-impl Rule {
-    fn by_removing_references(&self, references: &[usize]) -> Self {
-        match self {
-            Rule::Char(c) => Rule::Char(*c),
-            Rule::Matches(indices) => {
-                let contains = references.iter().any(|r| indices.contains(r));
-                if contains {
-                    Rule::None
-                } else {
-                    Rule::Matches(indices.clone())
-                }
-            }
-            Rule::MatchesOneOf(or_indices) => {
-                let new_or_indices = or_indices
-                    .iter()
-                    .filter(|inds| !references.iter().any(|r| inds.contains(r)))
-                    .cloned()
-                    .collect::<Vec<Vec<usize>>>();
-                if new_or_indices.is_empty() {
-                    Rule::None
-                } else {
-                    Rule::MatchesOneOf(new_or_indices)
-                }
-            }
-            Rule::None => Rule::None,
-        }
-    }
 }
 
 impl FromStr for Rule {
@@ -109,85 +82,370 @@ impl FromStr for RulesAndMessages {
     }
 }
 
-struct RegexGenerator {
-    max_depth: usize,
-    incestuous_depth_counter: HashMap<usize, usize>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Symbol {
+    Char(char),
+    Rule(usize),
 }
 
-impl RegexGenerator {
-    fn new(incestuous_rules: Vec<usize>, max_depth: usize) -> Self {
-        RegexGenerator {
-            max_depth,
-            incestuous_depth_counter: incestuous_rules.iter().map(|i| (*i, 0usize)).collect(),
+// Rule `rule`'s alternative `alt`, matched as far as `dot` starting from
+// `origin` in the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    alt: usize,
+    dot: usize,
+    origin: usize,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseTree {
+    Char(char),
+    Rule(usize, Vec<ParseTree>),
+}
+
+// As an S-expression, e.g. (0 (4 a) (5 b)).
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTree::Char(c) => write!(f, "{}", c),
+            ParseTree::Rule(rule, children) => {
+                write!(f, "({}", rule)?;
+                for child in children {
+                    write!(f, " {}", child)?;
+                }
+                write!(f, ")")
+            }
         }
     }
+}
+
+// Why a message didn't match: the furthest the parser got, what it could
+// have carried on with there, and what it found instead.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MatchError {
+    position: usize,
+    expected: Vec<char>,
+    found: Option<char>,
+}
 
-    fn default() -> Self {
-        RegexGenerator {
-            max_depth: 0,
-            incestuous_depth_counter: HashMap::new(),
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let found = self
+            .found
+            .map_or("the end of the message".to_string(), |c| format!("{:?}", c));
+        if self.expected.is_empty() {
+            write!(f, "unexpected {} at {}", found, self.position)
+        } else {
+            let expected = self
+                .expected
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .join(" or ");
+            write!(
+                f,
+                "expected {} at {}, found {}",
+                expected, self.position, found
+            )
+        }
+    }
+}
+
+// An Earley parser over the rules, so any rule can refer to any other,
+// including itself, on either side.
+struct Matcher {
+    alternatives: HashMap<usize, Vec<Vec<Symbol>>>,
+    nullable: HashSet<usize>,
+}
+
+struct Chart<'a> {
+    matcher: &'a Matcher,
+    start: usize,
+    message: Vec<char>,
+    items: Vec<Vec<Item>>,
+}
+
+impl Matcher {
+    fn new(rules: &RuleSet) -> Self {
+        let to_symbols = |indices: &Vec<usize>| indices.iter().map(|&i| Symbol::Rule(i)).collect();
+        let alternatives: HashMap<usize, Vec<Vec<Symbol>>> = rules
+            .iter()
+            .map(|(&index, rule)| {
+                let alts = match rule {
+                    Rule::Char(c) => vec![vec![Symbol::Char(*c)]],
+                    Rule::Matches(indices) => vec![to_symbols(indices)],
+                    Rule::MatchesOneOf(or_indices) => or_indices.iter().map(to_symbols).collect(),
+                };
+                (index, alts)
+            })
+            .collect();
+
+        // Rules that can match nothing at all, which Earley has to step over
+        // when it predicts them.
+        let mut nullable = HashSet::new();
+        loop {
+            let before = nullable.len();
+            for (&index, alts) in &alternatives {
+                let empty = alts.iter().any(|alt| {
+                    alt.iter()
+                        .all(|s| matches!(s, Symbol::Rule(r) if nullable.contains(r)))
+                });
+                if empty {
+                    nullable.insert(index);
+                }
+            }
+            if nullable.len() == before {
+                break;
+            }
+        }
+
+        Matcher {
+            alternatives,
+            nullable,
         }
     }
 
-    fn create_regex(&mut self, rules: &RuleSet, start: usize) -> Regex {
-        Regex::new(format!("^{}$", self.create_regex_string(rules, &start)).as_str()).unwrap()
+    fn alternatives(&self, rule: usize) -> &[Vec<Symbol>] {
+        self.alternatives.get(&rule).map_or(&[], |alts| alts)
     }
 
-    fn create_regex_string(&mut self, rules: &RuleSet, start: &usize) -> String {
-        let rule = &rules[start];
+    fn next_symbol(&self, item: &Item) -> Option<Symbol> {
+        self.alternatives(item.rule)[item.alt]
+            .get(item.dot)
+            .copied()
+    }
+
+    fn chart(&self, start: usize, message: &str) -> Chart<'_> {
+        let message: Vec<char> = message.chars().collect();
+        let mut items = vec![Vec::new(); message.len() + 1];
+        let mut seen = vec![HashSet::new(); message.len() + 1];
+        let mut add = |items: &mut Vec<Vec<Item>>, i: usize, item: Item| {
+            if seen[i].insert(item) {
+                items[i].push(item);
+            }
+        };
 
-        if let Some(c) = self.incestuous_depth_counter.get(start) {
-            if c > &self.max_depth {
-                self.incestuous_depth_counter.insert(*start, 0);
-                let references = self
-                    .incestuous_depth_counter
-                    .keys()
-                    .cloned()
-                    .collect::<Vec<_>>();
-                let adjusted_rule = rule.by_removing_references(&references);
-                return self.create_regex_string_for_rule(rules, &adjusted_rule);
+        for alt in 0..self.alternatives(start).len() {
+            add(
+                &mut items,
+                0,
+                Item {
+                    rule: start,
+                    alt,
+                    dot: 0,
+                    origin: 0,
+                },
+            );
+        }
+        for i in 0..=message.len() {
+            let mut k = 0;
+            while k < items[i].len() {
+                let item = items[i][k];
+                k += 1;
+                let advanced = Item {
+                    dot: item.dot + 1,
+                    ..item
+                };
+                match self.next_symbol(&item) {
+                    None => {
+                        let mut p = 0;
+                        while p < items[item.origin].len() {
+                            let parent = items[item.origin][p];
+                            p += 1;
+                            if self.next_symbol(&parent) == Some(Symbol::Rule(item.rule)) {
+                                add(
+                                    &mut items,
+                                    i,
+                                    Item {
+                                        dot: parent.dot + 1,
+                                        ..parent
+                                    },
+                                );
+                            }
+                        }
+                    }
+                    Some(Symbol::Rule(rule)) => {
+                        for alt in 0..self.alternatives(rule).len() {
+                            add(
+                                &mut items,
+                                i,
+                                Item {
+                                    rule,
+                                    alt,
+                                    dot: 0,
+                                    origin: i,
+                                },
+                            );
+                        }
+                        if self.nullable.contains(&rule) {
+                            add(&mut items, i, advanced);
+                        }
+                    }
+                    Some(Symbol::Char(c)) => {
+                        if message.get(i) == Some(&c) {
+                            add(&mut items, i + 1, advanced);
+                        }
+                    }
+                }
             }
-            *self.incestuous_depth_counter.entry(*start).or_insert(0) += 1;
         }
 
-        self.create_regex_string_for_rule(rules, rule)
+        Chart {
+            matcher: self,
+            start,
+            message,
+            items,
+        }
+    }
+
+    fn matches(&self, start: usize, message: &str) -> bool {
+        self.chart(start, message).accepted()
+    }
+}
+
+impl<'a> Chart<'a> {
+    fn is_complete(&self, item: &Item) -> bool {
+        self.matcher.next_symbol(item).is_none()
     }
 
-    fn create_regex_string_for_rule(&mut self, rules: &RuleSet, rule: &Rule) -> String {
-        match rule {
-            Rule::Char(c) => c.to_string(),
-            Rule::Matches(indices) => indices
+    fn accepted(&self) -> bool {
+        self.items[self.message.len()]
+            .iter()
+            .any(|item| item.rule == self.start && item.origin == 0 && self.is_complete(item))
+    }
+}
+
+// Every way a message parses, or the furthest it got before it stopped
+// matching, for working out why a rule set accepts what it does.
+#[allow(dead_code)]
+impl<'a> Chart<'a> {
+    fn explain(&self) -> Result<(), MatchError> {
+        if self.accepted() {
+            return Ok(());
+        }
+        let position = (0..self.items.len())
+            .rev()
+            .find(|&i| !self.items[i].is_empty())
+            .unwrap_or(0);
+        let expected = self.items[position]
+            .iter()
+            .filter_map(|item| match self.matcher.next_symbol(item) {
+                Some(Symbol::Char(c)) => Some(c),
+                _ => None,
+            })
+            .sorted()
+            .dedup()
+            .collect();
+        Err(MatchError {
+            position,
+            expected,
+            found: self.message.get(position).copied(),
+        })
+    }
+
+    // The ends of every way `rule` can match starting at `start`, with the
+    // alternative that did it.
+    fn completions(&self, rule: usize, start: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (start..self.items.len()).flat_map(move |end| {
+            self.items[end]
                 .iter()
-                .map(|i| self.create_regex_string(rules, i))
-                .join(""),
-            Rule::MatchesOneOf(or_indices) => {
-                let ors = or_indices
-                    .iter()
-                    .map(|i| {
-                        i.iter()
-                            .map(|i| self.create_regex_string(rules, i))
-                            .join("")
+                .filter(move |item| {
+                    item.rule == rule && item.origin == start && self.is_complete(item)
+                })
+                .map(move |item| (item.alt, end))
+        })
+    }
+
+    // Every parse of the whole message. A rule that can derive itself
+    // without consuming anything would give infinitely many, so those loops
+    // are cut short.
+    fn trees(&self) -> Vec<ParseTree> {
+        self.rule_trees(self.start, 0, self.message.len(), &mut HashSet::new())
+    }
+
+    fn rule_trees(
+        &self,
+        rule: usize,
+        start: usize,
+        end: usize,
+        active: &mut HashSet<(usize, usize, usize)>,
+    ) -> Vec<ParseTree> {
+        if !active.insert((rule, start, end)) {
+            return Vec::new();
+        }
+        let alts: Vec<usize> = self
+            .completions(rule, start)
+            .filter(|&(_, e)| e == end)
+            .map(|(alt, _)| alt)
+            .collect();
+        let trees = alts
+            .into_iter()
+            .flat_map(|alt| {
+                let symbols = &self.matcher.alternatives(rule)[alt];
+                self.sequences(symbols, start, end, active)
+            })
+            .map(|children| ParseTree::Rule(rule, children))
+            .collect();
+        active.remove(&(rule, start, end));
+        trees
+    }
+
+    fn sequences(
+        &self,
+        symbols: &[Symbol],
+        start: usize,
+        end: usize,
+        active: &mut HashSet<(usize, usize, usize)>,
+    ) -> Vec<Vec<ParseTree>> {
+        let (first, rest) = match symbols.split_first() {
+            Some(split) => split,
+            None => return if start == end { vec![vec![]] } else { vec![] },
+        };
+        let heads: Vec<(ParseTree, usize)> = match *first {
+            Symbol::Char(c) if start < end && self.message[start] == c => {
+                vec![(ParseTree::Char(c), start + 1)]
+            }
+            Symbol::Char(_) => vec![],
+            Symbol::Rule(rule) => {
+                let ends: Vec<usize> = self
+                    .completions(rule, start)
+                    .map(|(_, e)| e)
+                    .filter(|&e| e <= end)
+                    .sorted()
+                    .dedup()
+                    .collect();
+                ends.into_iter()
+                    .flat_map(|e| {
+                        self.rule_trees(rule, start, e, active)
+                            .into_iter()
+                            .map(move |tree| (tree, e))
                     })
-                    .join("|");
-                format!("({})", ors)
+                    .collect()
+            }
+        };
+        let mut all = Vec::new();
+        for (head, next) in heads {
+            for mut tail in self.sequences(rest, next, end, active) {
+                tail.insert(0, head.clone());
+                all.push(tail);
             }
-            Rule::None => "".to_string(),
         }
+        all
     }
 }
 
 fn part1(rules: &RuleSet, messages: &[String]) -> usize {
-    let mut generator = RegexGenerator::default();
-    let regex = generator.create_regex(rules, 0);
-    messages.iter().filter(|m| regex.is_match(m)).count()
+    let matcher = Matcher::new(rules);
+    messages.iter().filter(|m| matcher.matches(0, m)).count()
 }
 
+// The new rules loop back on themselves, which the matcher copes with as is.
 fn part2(rules: &mut RuleSet, messages: &[String]) -> usize {
-    let mut generator = RegexGenerator::new(vec![8, 11], 3);
     rules.insert(8, Rule::MatchesOneOf(vec![vec![42], vec![42, 8]]));
     rules.insert(11, Rule::MatchesOneOf(vec![vec![42, 31], vec![42, 11, 31]]));
-    let regex = generator.create_regex(rules, 0);
-    messages.iter().filter(|m| regex.is_match(m)).count()
+    part1(rules, messages)
 }
 
 fn main() -> Result<()> {
@@ -221,4 +479,58 @@ mod tests {
         assert_eq!(result, 12);
         Ok(())
     }
+
+    fn rule_set(s: &str) -> RuleSet {
+        s.lines()
+            .map(index_and_rule)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_trees() -> Result<()> {
+        let input = read_file("input/test/day19_1.txt")?;
+        let RulesAndMessages(rules, _) = input;
+        let matcher = Matcher::new(&rules);
+        let trees = matcher.chart(0, "ababbb").trees();
+        assert_eq!(
+            trees.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            vec!["(0 (4 a) (1 (3 (5 b) (4 a)) (2 (5 b) (5 b))) (5 b))"]
+        );
+
+        // 1: 1 1 | 2 is ambiguous, with one tree per way of bracketing
+        let matcher = Matcher::new(&rule_set("0: 1\n1: 1 1 | 2\n2: \"a\""));
+        assert_eq!(matcher.chart(0, "aaa").trees().len(), 2);
+        assert_eq!(matcher.chart(0, "aaaa").trees().len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_left_recursion() {
+        // 0 is any number of "a"s followed by a "b", built up from the left
+        let matcher = Matcher::new(&rule_set("0: 0 1 | 2\n1: \"a\"\n2: \"b\""));
+        assert!(matcher.matches(0, "baaa"));
+        assert!(matcher.matches(0, "b"));
+        assert!(!matcher.matches(0, "aab"));
+    }
+
+    #[test]
+    fn test_explain() -> Result<()> {
+        let input = read_file("input/test/day19_1.txt")?;
+        let RulesAndMessages(rules, _) = input;
+        let matcher = Matcher::new(&rules);
+        assert_eq!(matcher.chart(0, "abbbab").explain(), Ok(()));
+
+        let error = matcher.chart(0, "aaabbb").explain().unwrap_err();
+        assert_eq!(error.position, 4);
+        assert_eq!(error.to_string(), "expected 'a' at 4, found 'b'");
+        let error = matcher.chart(0, "aaaabbb").explain().unwrap_err();
+        assert_eq!(error.to_string(), "unexpected 'b' at 6");
+        let error = matcher.chart(0, "abab").explain().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected 'b' at 4, found the end of the message"
+        );
+        Ok(())
+    }
 }
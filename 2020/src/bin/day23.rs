@@ -1,14 +1,14 @@
+use utils::ring::Ring;
+
 type Cup = usize;
 
 fn parse(s: &str) -> Vec<Cup> {
     s.chars().map(|c| c.to_digit(10).unwrap() as Cup).collect()
 }
 
-fn play(cups: &[Cup], times: usize) -> Vec<Cup> {
+fn play(cups: &[Cup], times: usize) -> Ring<Cup> {
     let max = *cups.iter().max().unwrap();
-    let length = cups.len();
-    let mut cup_to_neighbor = vec![0; max + 1];
-    (0..length).for_each(|i| cup_to_neighbor[cups[i]] = cups[(i + 1) % length]);
+    let mut ring: Ring<Cup> = cups.iter().copied().collect();
 
     let minus_one = |c: Cup| -> Cup {
         match c - 1 {
@@ -16,37 +16,27 @@ fn play(cups: &[Cup], times: usize) -> Vec<Cup> {
             a => a,
         }
     };
-    let mut current_cup = cups[0];
+    let mut current = ring.head().unwrap();
     for _ in 0..times {
-        let take1 = cup_to_neighbor[current_cup];
-        let take2 = cup_to_neighbor[take1];
-        let take3 = cup_to_neighbor[take2];
-        cup_to_neighbor[current_cup] = cup_to_neighbor[take3];
+        let taken = ring.splice_out(current, 3);
 
-        let mut target = minus_one(current_cup);
-        while [take1, take2, take3].contains(&target) {
+        let mut target = minus_one(ring[current]);
+        while ring.segment(&taken).any(|&c| c == target) {
             target = minus_one(target);
         }
 
-        let temp = cup_to_neighbor[target];
-        cup_to_neighbor[target] = take1;
-        cup_to_neighbor[take3] = temp;
-
-        current_cup = cup_to_neighbor[current_cup];
+        ring.splice_in(ring.find(&target).unwrap(), taken);
+        current = ring.next(current);
     }
-    cup_to_neighbor
+    ring
 }
 
 fn part1(cups: &[Cup]) -> String {
-    let cup_to_neighbor = play(cups, 100);
-    let mut res = String::new();
-    let mut cup = 1;
-    while cup_to_neighbor[cup] != 1 {
-        let next_cup = cup_to_neighbor[cup];
-        res.push_str(&next_cup.to_string());
-        cup = next_cup;
-    }
-    res
+    let ring = play(cups, 100);
+    ring.iter_from(ring.find(&1).unwrap())
+        .skip(1)
+        .map(|c| c.to_string())
+        .collect()
 }
 
 fn part2(initial_cups: &[Cup]) -> usize {
@@ -55,10 +45,11 @@ fn part2(initial_cups: &[Cup]) -> usize {
         .copied()
         .chain(10usize..=1_000_000)
         .collect::<Vec<_>>();
-    let cup_to_neighbor = play(&cups, 10_000_000);
-    let after_one = cup_to_neighbor[1];
-    let after_that = cup_to_neighbor[after_one];
-    after_one * after_that
+    let ring = play(&cups, 10_000_000);
+    ring.iter_from(ring.find(&1).unwrap())
+        .skip(1)
+        .take(2)
+        .product()
 }
 
 fn main() {
@@ -1,39 +1,21 @@
-fn perform(value: usize, subject: usize, divisor: usize) -> usize {
-    (value * subject) % divisor
-}
-
-fn perform_times(subject: usize, divisor: usize, times: usize) -> usize {
-    (0..times).fold(1, |acc, _| perform(acc, subject, divisor))
-}
+use anyhow::{Context, Result};
+use aoc_lib::number_theory::{discrete_log, mod_pow};
 
-fn determine_loop_size(
-    subject: usize,
-    target: usize,
-    mut value: usize,
-    divisor: usize,
-    mut iteration: usize,
-) -> usize {
-    while value != target {
-        iteration += 1;
-        value = perform(value, subject, divisor);
-    }
-    iteration
-}
+const SUBJECT: i64 = 7;
+const DIVISOR: i64 = 20201227;
 
-fn part1(card: usize, door: usize) -> usize {
-    let divisor = 20201227;
-    let card_loop_size = determine_loop_size(7, card, 1, divisor, 0);
-    println!("card loop: {}", card_loop_size);
-    let door_loop_size = determine_loop_size(7, door, 1, divisor, 0);
-    println!("door loop: {}", door_loop_size);
-    let door_public_key = perform_times(7, divisor, door_loop_size);
-    perform_times(door_public_key, 20201227, card_loop_size)
+// A public key is SUBJECT^loop_size mod DIVISOR, so the loop size is its
+// discrete logarithm and the encryption key is the other public key raised to it.
+fn part1(card: i64, door: i64) -> Option<i64> {
+    let card_loop_size = discrete_log(SUBJECT, card, DIVISOR)?;
+    Some(mod_pow(door, card_loop_size, DIVISOR))
 }
 
-fn main() {
+fn main() -> Result<()> {
     let (card, door) = (5099500, 7648211);
-    let result = part1(door, card);
+    let result = part1(door, card).context("Couldn't find a loop size")?;
     println!("part 1: {}", result);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -44,6 +26,6 @@ mod tests {
     fn test_part1() {
         let (card, door) = (5764801, 17807724);
         let result = part1(card, door);
-        assert_eq!(result, 14897079);
+        assert_eq!(result, Some(14897079));
     }
 }
@@ -0,0 +1,298 @@
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+// Pointy-topped hexes, so rows run east-west and the six neighbours are e,
+// se, sw, w, nw and ne. The axial coordinates grow east (q) and south-east (r).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    Southeast,
+    Southwest,
+    West,
+    Northwest,
+    Northeast,
+}
+
+impl HexDirection {
+    // In the same order as the automaton's hex neighbourhood.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::Southeast,
+        HexDirection::Southwest,
+        HexDirection::West,
+        HexDirection::Northwest,
+        HexDirection::Northeast,
+    ];
+
+    pub fn offset(&self) -> Hex {
+        match self {
+            HexDirection::East => Hex::new(1, 0),
+            HexDirection::Southeast => Hex::new(0, 1),
+            HexDirection::Southwest => Hex::new(-1, 1),
+            HexDirection::West => Hex::new(-1, 0),
+            HexDirection::Northwest => Hex::new(0, -1),
+            HexDirection::Northeast => Hex::new(1, -1),
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|d| d == self).unwrap()
+    }
+
+    // Turns clockwise by `turns` sixths of a circle; negative turns go the
+    // other way.
+    pub fn rotate(&self, turns: i64) -> Self {
+        Self::ALL[(self.index() as i64 + turns).rem_euclid(6) as usize]
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "e" => HexDirection::East,
+            "se" => HexDirection::Southeast,
+            "sw" => HexDirection::Southwest,
+            "w" => HexDirection::West,
+            "nw" => HexDirection::Northwest,
+            "ne" => HexDirection::Northeast,
+            _ => bail!("{:?} isn't a hex direction", s),
+        })
+    }
+}
+
+// Splits an unseparated run of directions like "esenee" into e, se, ne, e.
+pub fn parse_directions(s: &str) -> Result<Vec<HexDirection>> {
+    let mut directions = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let len = if rest.starts_with('n') || rest.starts_with('s') {
+            2
+        } else {
+            1
+        };
+        // Count characters rather than bytes, so anything non-ASCII is a bad
+        // direction rather than a slice off a char boundary.
+        let end = rest
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(rest.len()))
+            .nth(len);
+        let end = match end {
+            Some(end) => end,
+            None => bail!("{:?} ends partway through a direction", s),
+        };
+        directions.push(rest[..end].parse()?);
+        rest = &rest[end..];
+    }
+    Ok(directions)
+}
+
+impl Hex {
+    pub fn new(q: i64, r: i64) -> Self {
+        Self { q, r }
+    }
+
+    pub fn from_cube(x: i64, y: i64, z: i64) -> Self {
+        assert_eq!(x + y + z, 0, "cube coordinates must sum to zero");
+        Self::new(x, z)
+    }
+
+    // (x, y, z) with x + y + z = 0.
+    pub fn to_cube(&self) -> (i64, i64, i64) {
+        (self.q, -self.q - self.r, self.r)
+    }
+
+    // "Odd-r" offset coordinates: (column, row) where odd rows are shoved half
+    // a hex to the east.
+    pub fn from_offset(column: i64, row: i64) -> Self {
+        Self::new(column - (row - (row & 1)) / 2, row)
+    }
+
+    pub fn to_offset(&self) -> (i64, i64) {
+        (self.q + (self.r - (self.r & 1)) / 2, self.r)
+    }
+
+    pub fn neighbor(&self, direction: HexDirection) -> Self {
+        *self + direction.offset()
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = Hex> + '_ {
+        HexDirection::ALL.iter().map(move |&d| self.neighbor(d))
+    }
+
+    // Where following every direction from here ends up.
+    pub fn walk<'a, I>(&self, directions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a HexDirection>,
+    {
+        directions
+            .into_iter()
+            .fold(*self, |hex, &direction| hex.neighbor(direction))
+    }
+
+    pub fn length(&self) -> i64 {
+        let (x, y, z) = self.to_cube();
+        (x.abs() + y.abs() + z.abs()) / 2
+    }
+
+    pub fn distance(&self, other: &Hex) -> i64 {
+        (*self - *other).length()
+    }
+
+    // Turns clockwise about the origin by `turns` sixths of a circle.
+    pub fn rotate(&self, turns: i64) -> Self {
+        let (mut x, mut y, mut z) = self.to_cube();
+        for _ in 0..turns.rem_euclid(6) {
+            (x, y, z) = (-z, -x, -y);
+        }
+        Self::from_cube(x, y, z)
+    }
+
+    pub fn rotate_around(&self, center: &Hex, turns: i64) -> Self {
+        (*self - *center).rotate(turns) + *center
+    }
+
+    // Every hex exactly `radius` away, going clockwise from the one furthest
+    // to the west.
+    pub fn ring(&self, radius: i64) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut hex = *self + HexDirection::West.offset() * radius;
+        let mut ring = Vec::new();
+        for direction in HexDirection::ALL.iter().map(|d| d.rotate(-1)) {
+            for _ in 0..radius {
+                ring.push(hex);
+                hex = hex.neighbor(direction);
+            }
+        }
+        ring
+    }
+
+    // Every hex within `radius`, nearest rings first.
+    pub fn spiral(&self, radius: i64) -> Vec<Hex> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+
+    // The point the automaton uses for this hex, with Neighbourhood::hex().
+    pub fn to_point(&self) -> [i64; 2] {
+        [self.q, self.r]
+    }
+
+    pub fn from_point([q, r]: [i64; 2]) -> Self {
+        Self::new(q, r)
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, other: Hex) -> Hex {
+        Hex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, other: Hex) -> Hex {
+        Hex::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Hex;
+
+    fn mul(self, scale: i64) -> Hex {
+        Hex::new(self.q * scale, self.r * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_lib::automaton::Neighbourhood;
+
+    #[test]
+    fn test_parsing() -> Result<()> {
+        let directions = parse_directions("esenee")?;
+        assert_eq!(
+            directions,
+            vec![
+                HexDirection::East,
+                HexDirection::Southeast,
+                HexDirection::Northeast,
+                HexDirection::East
+            ]
+        );
+        assert_eq!(Hex::default().walk(&directions), Hex::new(3, 0));
+        assert_eq!(
+            Hex::default().walk(&parse_directions("nwwswee")?),
+            Hex::default()
+        );
+        assert!(parse_directions("esn").is_err());
+        assert!(parse_directions("ex").is_err());
+        assert!(parse_directions("é").is_err());
+        assert!(parse_directions("nλ").is_err());
+        assert!(parse_directions("sé").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_conversions() {
+        for hex in Hex::default().spiral(3) {
+            let (x, y, z) = hex.to_cube();
+            assert_eq!(Hex::from_cube(x, y, z), hex);
+            let (column, row) = hex.to_offset();
+            assert_eq!(Hex::from_offset(column, row), hex);
+            assert_eq!(Hex::from_point(hex.to_point()), hex);
+        }
+        // odd rows sit half a hex east, so south-east of (0, 0) is (0, 1)
+        assert_eq!(Hex::new(0, 1).to_offset(), (0, 1));
+        assert_eq!(Hex::new(0, 2).to_offset(), (1, 2));
+    }
+
+    #[test]
+    fn test_distance_and_rings() {
+        assert_eq!(Hex::new(3, 0).length(), 3);
+        assert_eq!(Hex::new(2, -5).distance(&Hex::new(-1, 1)), 6);
+        for radius in 0..4 {
+            let ring = Hex::new(2, 1).ring(radius);
+            assert_eq!(ring.len(), (6 * radius).max(1) as usize);
+            assert!(ring.iter().all(|h| h.distance(&Hex::new(2, 1)) == radius));
+        }
+        assert_eq!(Hex::default().spiral(2).len(), 19);
+        let neighbourhood: Vec<_> = Neighbourhood::hex()
+            .around([0, 0])
+            .map(Hex::from_point)
+            .collect();
+        assert_eq!(
+            neighbourhood,
+            Hex::default().neighbors().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_rotation() {
+        let east = Hex::new(1, 0);
+        assert_eq!(east.rotate(1), HexDirection::Southeast.offset());
+        assert_eq!(east.rotate(-1), HexDirection::Northeast.offset());
+        assert_eq!(east.rotate(6), east);
+        assert_eq!(Hex::new(3, -1).rotate(3), Hex::new(-3, 1));
+        assert_eq!(HexDirection::West.rotate(2), HexDirection::Northeast);
+        assert_eq!(
+            Hex::new(3, 1).rotate_around(&Hex::new(2, 1), 2),
+            Hex::new(1, 2)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use aoc_lib::grid::DenseGrid;
+
+// A rectangular pattern where `None` cells match anything.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern<T> {
+    cells: DenseGrid<Option<T>>,
+}
+
+// Where a pattern was found: the top left corner of its bounding box, which of
+// the pattern's orientations matched, and every non-wildcard cell it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub position: (usize, usize),
+    pub orientation: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Pattern<char> {
+    // One line per row. Lines can be ragged; anything missing off the end of
+    // a short line is a wildcard.
+    pub fn parse(s: &str, wildcard: char) -> Self {
+        let lines: Vec<&str> = s.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let rows = lines
+            .iter()
+            .map(|line| {
+                let mut row: Vec<_> = line
+                    .chars()
+                    .map(|c| Some(c).filter(|&c| c != wildcard))
+                    .collect();
+                row.resize(width, None);
+                row
+            })
+            .collect();
+        Self::new(DenseGrid::from_rows(rows))
+    }
+}
+
+impl<T: Clone + PartialEq> Pattern<T> {
+    pub fn new(cells: DenseGrid<Option<T>>) -> Self {
+        Self { cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    // The eight orientations from DenseGrid::orientations, minus any repeats for
+    // patterns with symmetries, paired with their orientation number.
+    pub fn orientations(&self) -> Vec<(usize, Self)> {
+        let mut seen = Vec::new();
+        self.cells
+            .orientations()
+            .enumerate()
+            .filter(|(_, cells)| {
+                let new = !seen.contains(cells);
+                if new {
+                    seen.push(cells.clone());
+                }
+                new
+            })
+            .map(|(i, cells)| (i, Self::new(cells)))
+            .collect()
+    }
+
+    fn matches_at(&self, grid: &DenseGrid<T>, x: usize, y: usize) -> bool {
+        self.cells
+            .iter()
+            .all(|((dx, dy), cell)| cell.as_ref().is_none_or(|c| &grid[(x + dx, y + dy)] == c))
+    }
+
+    fn found_at(&self, x: usize, y: usize, orientation: usize) -> Match {
+        let cells = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.is_some())
+            .map(|((dx, dy), _)| (x + dx, y + dy))
+            .collect();
+        Match {
+            position: (x, y),
+            orientation,
+            cells,
+        }
+    }
+
+    // Every match of the pattern as it is, including ones that overlap.
+    pub fn find(&self, grid: &DenseGrid<T>) -> Vec<Match> {
+        self.find_oriented(grid, 0)
+    }
+
+    fn find_oriented(&self, grid: &DenseGrid<T>, orientation: usize) -> Vec<Match> {
+        if self.width() > grid.width() || self.height() > grid.height() {
+            return Vec::new();
+        }
+        (0..=grid.height() - self.height())
+            .flat_map(|y| (0..=grid.width() - self.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.matches_at(grid, x, y))
+            .map(|(x, y)| self.found_at(x, y, orientation))
+            .collect()
+    }
+
+    // Every match of the pattern in any of its orientations. Turning the
+    // pattern instead of the grid keeps the positions in grid coordinates.
+    pub fn find_any_orientation(&self, grid: &DenseGrid<T>) -> Vec<Match> {
+        self.orientations()
+            .into_iter()
+            .flat_map(|(i, pattern)| pattern.find_oriented(grid, i))
+            .collect()
+    }
+}
+
+// Every cell covered by at least one of the matches.
+pub fn covered_cells(matches: &[Match]) -> HashSet<(usize, usize)> {
+    matches
+        .iter()
+        .flat_map(|m| m.cells.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> DenseGrid<char> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_find() {
+        let pattern = Pattern::parse("#.\n##", '.');
+        assert_eq!(pattern.width(), 2);
+        let image = grid("#..\n##.\n###");
+        let matches = pattern.find(&image);
+        assert_eq!(
+            matches.iter().map(|m| m.position).collect::<Vec<_>>(),
+            vec![(0, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(matches[0].cells, vec![(0, 0), (0, 1), (1, 1)]);
+        // (0, 1) and (1, 1) overlap with the first one
+        assert_eq!(covered_cells(&matches).len(), 6);
+    }
+
+    #[test]
+    fn test_ragged_and_too_big() {
+        let pattern = Pattern::parse("#\n##", ' ');
+        assert_eq!(pattern, Pattern::parse("# \n##", ' '));
+        assert!(pattern.find(&grid("#")).is_empty());
+    }
+
+    #[test]
+    fn test_orientations() {
+        // an L has no symmetry, so all eight orientations are different
+        let l = Pattern::parse("#.\n#.\n##", '.');
+        assert_eq!(l.orientations().len(), 8);
+        let square = Pattern::parse("##\n##", '.');
+        assert_eq!(square.orientations().len(), 1);
+
+        let image = grid("...\n###\n#..");
+        let matches = l.find_any_orientation(&image);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].position, (0, 1));
+        assert_eq!(matches[0].cells.len(), 4);
+        assert!(l.find(&image).is_empty());
+    }
+}
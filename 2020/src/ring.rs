@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+// A circular doubly-linked list kept in a Vec, for the games where things get
+// picked up and put down somewhere else in a circle. Nodes are referred to by
+// their index in the Vec, which never changes, so a handle stays good while
+// its node is moved around. Values should be unique for `find` to make sense.
+// Removed nodes' slots go on a free list and get reused by later inserts, so a
+// handle is only good until its node is removed.
+#[derive(Debug, Clone)]
+pub struct Ring<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+    index: HashMap<T, usize>,
+    head: Option<usize>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    next: usize,
+    prev: usize,
+}
+
+// A run of nodes that has been cut out of the ring. They stay linked to each
+// other, so the whole run can be put back in one go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    first: usize,
+    last: usize,
+    len: usize,
+}
+
+impl<T: Clone + Eq + Hash> Ring<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            index: HashMap::with_capacity(capacity),
+            head: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Where the ring was started, for walking the whole thing. Moves on if
+    // that node is removed.
+    pub fn head(&self) -> Option<usize> {
+        self.head
+    }
+
+    pub fn find(&self, value: &T) -> Option<usize> {
+        self.index.get(value).copied()
+    }
+
+    pub fn next(&self, handle: usize) -> usize {
+        self.nodes[handle].next
+    }
+
+    pub fn prev(&self, handle: usize) -> usize {
+        self.nodes[handle].prev
+    }
+
+    // Steps forwards for positive `steps` and backwards for negative ones.
+    pub fn walk(&self, handle: usize, steps: i64) -> usize {
+        let mut handle = handle;
+        for _ in 0..steps.abs() {
+            handle = if steps > 0 {
+                self.next(handle)
+            } else {
+                self.prev(handle)
+            };
+        }
+        handle
+    }
+
+    fn new_node(&mut self, value: T) -> usize {
+        let handle = self.free.pop().unwrap_or(self.nodes.len());
+        self.index.insert(value.clone(), handle);
+        let node = Node {
+            value,
+            next: handle,
+            prev: handle,
+        };
+        match self.nodes.get_mut(handle) {
+            Some(slot) => *slot = node,
+            None => self.nodes.push(node),
+        }
+        handle
+    }
+
+    // Joins a detached run of nodes back in straight after `after`.
+    fn link(&mut self, after: usize, first: usize, last: usize) {
+        let next = self.nodes[after].next;
+        self.nodes[after].next = first;
+        self.nodes[first].prev = after;
+        self.nodes[last].next = next;
+        self.nodes[next].prev = last;
+    }
+
+    fn unlink(&mut self, first: usize, last: usize) {
+        let prev = self.nodes[first].prev;
+        let next = self.nodes[last].next;
+        self.nodes[prev].next = next;
+        self.nodes[next].prev = prev;
+    }
+
+    // Adds a value at the end, just before the head.
+    pub fn push(&mut self, value: T) -> usize {
+        let handle = self.new_node(value);
+        match self.head {
+            Some(head) => {
+                let last = self.prev(head);
+                self.link(last, handle, handle);
+            }
+            None => self.head = Some(handle),
+        }
+        self.len += 1;
+        handle
+    }
+
+    pub fn insert_after(&mut self, after: usize, value: T) -> usize {
+        let handle = self.new_node(value);
+        self.link(after, handle, handle);
+        self.len += 1;
+        handle
+    }
+
+    pub fn insert_before(&mut self, before: usize, value: T) -> usize {
+        let prev = self.prev(before);
+        self.insert_after(prev, value)
+    }
+
+    // Takes a node out for good, returning its value.
+    pub fn remove(&mut self, handle: usize) -> T {
+        self.unlink(handle, handle);
+        self.len -= 1;
+        if self.head == Some(handle) {
+            self.head = (self.len > 0).then(|| self.nodes[handle].next);
+        }
+        let value = self.nodes[handle].value.clone();
+        self.index.remove(&value);
+        self.free.push(handle);
+        value
+    }
+
+    // Moves a node so it comes straight after `after`.
+    pub fn move_after(&mut self, handle: usize, after: usize) {
+        if handle == after {
+            return;
+        }
+        if self.head == Some(handle) {
+            self.head = Some(self.nodes[handle].next);
+        }
+        self.unlink(handle, handle);
+        self.link(after, handle, handle);
+    }
+
+    // Cuts out the `count` nodes following `after`, which must leave at least
+    // `after` behind.
+    pub fn splice_out(&mut self, after: usize, count: usize) -> Segment {
+        assert!(count > 0 && count < self.len, "can't splice out {}", count);
+        let first = self.next(after);
+        let last = self.walk(first, count as i64 - 1);
+        if let Some(head) = self.head {
+            if self.segment_handles(first, count).any(|h| h == head) {
+                self.head = Some(self.next(last));
+            }
+        }
+        self.unlink(first, last);
+        self.len -= count;
+        Segment {
+            first,
+            last,
+            len: count,
+        }
+    }
+
+    // Puts a segment from `splice_out` back in after `after`, which mustn't
+    // be part of the segment itself.
+    pub fn splice_in(&mut self, after: usize, segment: Segment) {
+        self.link(after, segment.first, segment.last);
+        self.len += segment.len;
+    }
+
+    fn segment_handles(&self, first: usize, len: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(first), move |&h| Some(self.next(h))).take(len)
+    }
+
+    pub fn segment(&self, segment: &Segment) -> impl Iterator<Item = &T> + '_ {
+        self.segment_handles(segment.first, segment.len)
+            .map(move |h| &self.nodes[h].value)
+    }
+
+    // Every value once round, going forwards from `handle`.
+    pub fn iter_from(&self, handle: usize) -> impl Iterator<Item = &T> + '_ {
+        self.segment_handles(handle, self.len)
+            .map(move |h| &self.nodes[h].value)
+    }
+
+    // Every value once round, going backwards from `handle`.
+    pub fn iter_back_from(&self, handle: usize) -> impl Iterator<Item = &T> + '_ {
+        std::iter::successors(Some(handle), move |&h| Some(self.prev(h)))
+            .take(self.len)
+            .map(move |h| &self.nodes[h].value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.head
+            .into_iter()
+            .flat_map(move |head| self.iter_from(head))
+    }
+}
+
+impl<T: Clone + Eq + Hash> Default for Ring<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Eq + Hash> FromIterator<T> for Ring<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut ring = Self::with_capacity(iter.size_hint().0);
+        for value in iter {
+            ring.push(value);
+        }
+        ring
+    }
+}
+
+impl<T> Index<usize> for Ring<T> {
+    type Output = T;
+
+    fn index(&self, handle: usize) -> &T {
+        &self.nodes[handle].value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(ring: &Ring<u32>) -> Vec<u32> {
+        ring.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_inserting_and_walking() {
+        let mut ring: Ring<u32> = (1..=4).collect();
+        assert_eq!(values(&ring), vec![1, 2, 3, 4]);
+        let two = ring.find(&2).unwrap();
+        ring.insert_after(two, 5);
+        ring.insert_before(two, 6);
+        assert_eq!(values(&ring), vec![1, 6, 2, 5, 3, 4]);
+        assert_eq!(ring[ring.walk(two, 3)], 4);
+        assert_eq!(ring[ring.walk(two, -3)], 4);
+        assert_eq!(ring[ring.walk(two, 6)], 2);
+        assert_eq!(
+            ring.iter_back_from(two).copied().collect::<Vec<_>>(),
+            vec![2, 6, 1, 4, 3, 5]
+        );
+    }
+
+    #[test]
+    fn test_removing() {
+        let mut ring: Ring<u32> = (1..=4).collect();
+        let head = ring.head().unwrap();
+        assert_eq!(ring.remove(head), 1);
+        assert_eq!(ring.find(&1), None);
+        assert_eq!(values(&ring), vec![2, 3, 4]);
+        let three = ring.find(&3).unwrap();
+        ring.move_after(three, ring.find(&4).unwrap());
+        assert_eq!(values(&ring), vec![2, 4, 3]);
+        for value in [2, 3, 4] {
+            ring.remove(ring.find(&value).unwrap());
+        }
+        assert!(ring.is_empty());
+        assert_eq!(values(&ring), vec![]);
+    }
+
+    #[test]
+    fn test_reusing_removed_slots() {
+        let mut ring: Ring<u32> = (1..=4).collect();
+        let two = ring.find(&2).unwrap();
+        ring.remove(two);
+        let five = ring.insert_after(ring.find(&4).unwrap(), 5);
+        assert_eq!(five, two);
+        assert_eq!(ring.nodes.len(), 4);
+        assert_eq!(values(&ring), vec![1, 3, 4, 5]);
+        assert_eq!(ring.find(&5), Some(five));
+        assert_eq!(ring.find(&2), None);
+    }
+
+    #[test]
+    fn test_splicing() {
+        let mut ring: Ring<u32> = (1..=6).collect();
+        let segment = ring.splice_out(ring.find(&5).unwrap(), 3);
+        assert_eq!(
+            ring.segment(&segment).copied().collect::<Vec<_>>(),
+            vec![6, 1, 2]
+        );
+        assert_eq!(ring.len(), 3);
+        assert_eq!(values(&ring), vec![3, 4, 5]);
+        ring.splice_in(ring.find(&3).unwrap(), segment);
+        assert_eq!(values(&ring), vec![3, 6, 1, 2, 4, 5]);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod hex;
+pub mod pattern;
+pub mod ring;
+
 use anyhow::Result;
 use std::fmt;
 use std::fs;
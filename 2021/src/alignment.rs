@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Mul;
+
+use itertools::Itertools;
+
+pub type Point = [i64; 3];
+
+// One of the 24 ways to turn a cube in place, as a matrix of -1, 0 and 1 with
+// exactly one non-zero entry in each row and column and a determinant of 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation([[i64; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    // Every signed permutation matrix that doesn't mirror, identity first.
+    pub fn all() -> Vec<Rotation> {
+        (0..3)
+            .permutations(3)
+            .cartesian_product(0..8)
+            .map(|(axes, signs)| {
+                let mut matrix = [[0; 3]; 3];
+                for (row, &axis) in axes.iter().enumerate() {
+                    matrix[row][axis] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                Rotation(matrix)
+            })
+            .filter(|r| r.determinant() == 1)
+            .collect()
+    }
+
+    fn determinant(&self) -> i64 {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply(&self, point: &Point) -> Point {
+        let mut result = [0; 3];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = (0..3).map(|i| self.0[row][i] * point[i]).sum();
+        }
+        result
+    }
+
+    // Rotation matrices are orthogonal, so the transpose undoes them.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = self.0[column][row];
+            }
+        }
+        Rotation(matrix)
+    }
+}
+
+// `a * b` turns by `b` first, then by `a`.
+impl Mul for Rotation {
+    type Output = Rotation;
+
+    fn mul(self, other: Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..3).map(|i| self.0[row][i] * other.0[i][column]).sum();
+            }
+        }
+        Rotation(matrix)
+    }
+}
+
+fn add(a: &Point, b: &Point) -> Point {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: &Point, b: &Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+// Moves points from one frame of reference into another: rotate, then
+// translate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub rotation: Rotation,
+    pub translation: Point,
+}
+
+impl Placement {
+    pub const IDENTITY: Placement = Placement {
+        rotation: Rotation::IDENTITY,
+        translation: [0, 0, 0],
+    };
+
+    pub fn apply(&self, point: &Point) -> Point {
+        add(&self.rotation.apply(point), &self.translation)
+    }
+
+    // The placement that applies `inner` and then `self`.
+    pub fn after(&self, inner: &Placement) -> Placement {
+        Placement {
+            rotation: self.rotation * inner.rotation,
+            translation: self.apply(&inner.translation),
+        }
+    }
+
+    pub fn inverse(&self) -> Placement {
+        let rotation = self.rotation.inverse();
+        Placement {
+            rotation,
+            translation: rotation.apply(&sub(&[0, 0, 0], &self.translation)),
+        }
+    }
+}
+
+// Squared distances between every pair of points. These don't change under
+// rotation or translation, so two clouds that share K points share at least
+// K * (K - 1) / 2 of them.
+type Fingerprint = HashMap<i64, Vec<(usize, usize)>>;
+
+fn fingerprints(points: &[Point]) -> Fingerprint {
+    let mut distances: Fingerprint = HashMap::new();
+    for (i, j) in (0..points.len()).tuple_combinations() {
+        let d = sub(&points[i], &points[j]);
+        distances
+            .entry(d.iter().map(|v| v * v).sum())
+            .or_default()
+            .push((i, j));
+    }
+    distances
+}
+
+// The points of `fingerprint` that are an end of some pair whose distance
+// also appears in `other`, along with how many distances the two share.
+fn shared_points(fingerprint: &Fingerprint, other: &Fingerprint) -> (HashSet<usize>, usize) {
+    let mut points = HashSet::new();
+    let mut shared = 0;
+    for (distance, pairs) in fingerprint {
+        if let Some(other_pairs) = other.get(distance) {
+            shared += pairs.len().min(other_pairs.len());
+            points.extend(pairs.iter().flat_map(|&(i, j)| [i, j]));
+        }
+    }
+    (points, shared)
+}
+
+fn align_fingerprinted(
+    reference: (&[Point], &Fingerprint),
+    other: (&[Point], &Fingerprint),
+    min_matches: usize,
+) -> Option<Placement> {
+    let (reference_candidates, shared) = shared_points(reference.1, other.1);
+    if shared < min_matches * min_matches.saturating_sub(1) / 2 {
+        return None;
+    }
+    let (other_candidates, _) = shared_points(other.1, reference.1);
+    let reference_set: HashSet<&Point> = reference.0.iter().collect();
+    Rotation::all().into_iter().find_map(|rotation| {
+        let rotated: Vec<Point> = other_candidates
+            .iter()
+            .map(|&i| rotation.apply(&other.0[i]))
+            .collect();
+        reference_candidates
+            .iter()
+            .cartesian_product(rotated.iter())
+            .map(|(&r, o)| sub(&reference.0[r], o))
+            .counts()
+            .into_iter()
+            .filter(|&(_, count)| count >= min_matches)
+            .map(|(translation, _)| Placement {
+                rotation,
+                translation,
+            })
+            .find(|placement| {
+                other
+                    .0
+                    .iter()
+                    .filter(|p| reference_set.contains(&placement.apply(p)))
+                    .count()
+                    >= min_matches
+            })
+    })
+}
+
+// Finds where `other` sits relative to `reference`, if at least `min_matches`
+// of its points line up with points of `reference` once placed.
+pub fn align(reference: &[Point], other: &[Point], min_matches: usize) -> Option<Placement> {
+    align_fingerprinted(
+        (reference, &fingerprints(reference)),
+        (other, &fingerprints(other)),
+        min_matches,
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacementGraph {
+    // (i, j) -> the placement taking cloud j's points into cloud i's frame,
+    // for every pair that overlaps. Both directions are present.
+    pub edges: HashMap<(usize, usize), Placement>,
+    // Where every cloud sits in cloud 0's frame, if it's connected to it.
+    pub placements: Vec<Option<Placement>>,
+}
+
+impl PlacementGraph {
+    pub fn neighbours(&self, cloud: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .keys()
+            .filter(move |&&(i, _)| i == cloud)
+            .map(|&(_, j)| j)
+    }
+}
+
+// Aligns every pair of clouds it can, then walks outwards from cloud 0 to put
+// everything in its frame.
+pub fn place_all(clouds: &[Vec<Point>], min_matches: usize) -> PlacementGraph {
+    let fingerprinted: Vec<_> = clouds
+        .iter()
+        .map(|cloud| (cloud.as_slice(), fingerprints(cloud)))
+        .collect();
+    let mut edges = HashMap::new();
+    for (i, j) in (0..clouds.len()).tuple_combinations() {
+        let reference = (fingerprinted[i].0, &fingerprinted[i].1);
+        let other = (fingerprinted[j].0, &fingerprinted[j].1);
+        if let Some(placement) = align_fingerprinted(reference, other, min_matches) {
+            edges.insert((j, i), placement.inverse());
+            edges.insert((i, j), placement);
+        }
+    }
+
+    let mut placements = vec![None; clouds.len()];
+    let mut queue = VecDeque::new();
+    if !clouds.is_empty() {
+        placements[0] = Some(Placement::IDENTITY);
+        queue.push_back(0);
+    }
+    while let Some(i) = queue.pop_front() {
+        let outer = placements[i].unwrap();
+        for (&(_, j), inner) in edges.iter().filter(|(&(from, _), _)| from == i) {
+            if placements[j].is_none() {
+                placements[j] = Some(outer.after(inner));
+                queue.push_back(j);
+            }
+        }
+    }
+    PlacementGraph { edges, placements }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_group() {
+        let all = Rotation::all();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], Rotation::IDENTITY);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), 24);
+        for &a in &all {
+            assert_eq!(a * a.inverse(), Rotation::IDENTITY);
+            for &b in &all {
+                assert!(all.contains(&(a * b)));
+                assert_eq!((a * b).apply(&[1, 2, 3]), a.apply(&b.apply(&[1, 2, 3])));
+            }
+        }
+        // every rotation sends (1, 2, 3) somewhere different
+        let images: HashSet<_> = all.iter().map(|r| r.apply(&[1, 2, 3])).collect();
+        assert_eq!(images.len(), 24);
+    }
+
+    #[test]
+    fn test_placements() {
+        let placement = Placement {
+            rotation: Rotation::all()[7],
+            translation: [5, -3, 10],
+        };
+        let p = [4, 8, -2];
+        assert_eq!(placement.inverse().apply(&placement.apply(&p)), p);
+        let twice = placement.after(&placement);
+        assert_eq!(twice.apply(&p), placement.apply(&placement.apply(&p)));
+    }
+
+    #[test]
+    fn test_align() {
+        let reference: Vec<Point> = vec![
+            [0, 0, 0],
+            [10, 0, 3],
+            [4, 17, -2],
+            [-6, 5, 9],
+            [13, -8, 1],
+            [2, 2, 25],
+            [-20, 1, 1],
+        ];
+        let hidden = Placement {
+            rotation: Rotation::all()[13],
+            translation: [100, -40, 7],
+        };
+        // the last two points are only seen by `other`
+        let mut other: Vec<Point> = reference[..5]
+            .iter()
+            .map(|p| hidden.inverse().apply(p))
+            .collect();
+        other.push([50, 50, 50]);
+        other.push([-30, 2, 60]);
+        assert_eq!(align(&reference, &other, 5), Some(hidden));
+        assert_eq!(align(&reference, &other, 6), None);
+
+        let graph = place_all(&[reference.clone(), other.clone(), vec![[1, 1, 1]]], 5);
+        assert_eq!(graph.placements[0], Some(Placement::IDENTITY));
+        assert_eq!(graph.placements[1], Some(hidden));
+        assert_eq!(graph.placements[2], None);
+        assert_eq!(graph.edges[&(1, 0)], hidden.inverse());
+        assert_eq!(graph.neighbours(0).collect::<Vec<_>>(), vec![1]);
+    }
+}
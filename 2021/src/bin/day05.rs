@@ -1,7 +1,7 @@
-use gcd::Gcd;
 use std::collections::HashSet;
 
 use anyhow::Result;
+use aoc_lib::number_theory::gcd;
 use itertools::Itertools;
 use utils::read_lines;
 
@@ -26,7 +26,7 @@ fn lattice_points(line: &Line) -> HashSet<Point> {
     // Figure out the slope as a reduced fraction (rise/run)
     let run = line.end.0 - line.start.0;
     let rise = line.end.1 - line.start.1;
-    let factor = (rise.abs() as usize).gcd(run.abs() as usize) as isize;
+    let factor = gcd(rise as i64, run as i64) as isize;
     let run = run / factor;
     let rise = rise / factor;
     // Either it's going up or it's going down - chaining together will catch the right one.
@@ -1,86 +1,81 @@
-use std::collections::{HashMap, HashSet};
-
 use anyhow::Result;
-use itertools::Itertools;
-use utils::read_grid;
-
-type Point = (i32, i32);
-type OctopusConfiguration = HashMap<Point, u8>;
-
-struct OctopusConfigurationStepper {
-    config: OctopusConfiguration,
-    keys: HashSet<Point>,
-}
+use aoc_lib::automaton::{Automaton, DenseAutomaton, Neighbourhood};
+use utils::read_lines;
 
-fn neighbor_points(point: &Point) -> Vec<Point> {
-    (-1..=1)
-        .cartesian_product(-1..=1)
-        .map(|(x, y)| (point.0 + x, point.1 + y))
-        .filter(|&(x, y)| x != point.0 || y != point.1)
-        .collect()
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Octopus {
+    energy: u8,
+    flashed: bool,
 }
 
-impl OctopusConfigurationStepper {
-    fn new(config: OctopusConfiguration) -> Self {
-        let keys = config.keys().cloned().collect();
-        Self { config, keys }
+impl Octopus {
+    fn is_flashing(&self) -> bool {
+        self.energy > 9 && !self.flashed
     }
+}
 
-    fn step(&mut self) {
-        for &p in self.keys.iter() {
-            self.config.insert(p, self.config[&p] + 1);
-        }
-        while self.resolve_flashes() {}
-    }
+type OctopusConfiguration = DenseAutomaton<Octopus>;
 
-    fn resolve_flashes(&mut self) -> bool {
-        let mut octopodes_to_adjust = vec![];
-        for &p in self.keys.iter() {
-            if self.config[&p] > 9 {
-                octopodes_to_adjust.push(p);
-                self.config.insert(p, 0);
-            }
+// Runs one step and returns how many octopodes flashed during it.
+fn step(octopodes: &mut OctopusConfiguration) -> usize {
+    octopodes.step(|o, _| Octopus {
+        energy: o.energy + 1,
+        flashed: false,
+    });
+    // Every octopus that is flashing right now is marked as flashed, and
+    // feeds one energy to each neighbour, until no new flashes happen.
+    octopodes.run_until_stable(|o, neighbours| {
+        if o.flashed {
+            return *o;
         }
-        for p in octopodes_to_adjust
-            .iter()
-            .flat_map(|&p| neighbor_points(&p))
-        {
-            if self.config.contains_key(&p) && self.config[&p] != 0 {
-                self.config.insert(p, self.config[&p] + 1);
-            }
+        let flashes = neighbours.iter().filter(|n| n.is_flashing()).count();
+        Octopus {
+            energy: o.energy + flashes as u8,
+            flashed: o.is_flashing(),
         }
-        !octopodes_to_adjust.is_empty()
-    }
-
-    fn count_recent_flashes(&self) -> usize {
-        self.config.values().filter(|&&o| o == 0).count()
-    }
+    });
+    octopodes.step(|o, _| Octopus {
+        energy: if o.flashed { 0 } else { o.energy },
+        flashed: o.flashed,
+    });
+    octopodes.cells().iter().filter(|o| o.flashed).count()
 }
 
-fn part1(octopodes: &mut OctopusConfigurationStepper) -> usize {
-    let mut count = 0;
-    for _ in 0..100 {
-        octopodes.step();
-        count += octopodes.count_recent_flashes();
-    }
-    count
+fn part1(octopodes: &mut OctopusConfiguration) -> usize {
+    (0..100).map(|_| step(octopodes)).sum()
 }
 
-fn part2(octopodes: &mut OctopusConfigurationStepper) -> usize {
-    let mut count = 0;
-    let size = octopodes.config.len();
-    while octopodes.count_recent_flashes() != size {
-        octopodes.step();
+fn part2(octopodes: &mut OctopusConfiguration) -> usize {
+    let size = octopodes.cells().len();
+    let mut count = 1;
+    while step(octopodes) != size {
         count += 1;
     }
     count
 }
 
+fn read_input(path: &str) -> Result<OctopusConfiguration> {
+    let rows = read_lines::<String>(path)?
+        .iter()
+        .map(|l| {
+            l.chars()
+                .map(|c| {
+                    Ok(Octopus {
+                        energy: c.to_string().parse()?,
+                        flashed: false,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DenseAutomaton::new(rows, &Neighbourhood::moore()))
+}
+
 fn main() -> Result<()> {
-    let mut octopodes = OctopusConfigurationStepper::new(read_grid("input/day11.txt")?);
+    let mut octopodes = read_input("input/day11.txt")?;
     let result = part1(&mut octopodes);
     println!("part 1: {}", result);
-    let mut octopodes = OctopusConfigurationStepper::new(read_grid("input/day11.txt")?);
+    let mut octopodes = read_input("input/day11.txt")?;
     let result = part2(&mut octopodes);
     println!("part 2: {}", result);
     Ok(())
@@ -88,10 +83,10 @@ fn main() -> Result<()> {
 
 #[test]
 fn test() -> Result<()> {
-    let mut octopodes = OctopusConfigurationStepper::new(read_grid("input/test/day11.txt")?);
+    let mut octopodes = read_input("input/test/day11.txt")?;
     let result = part1(&mut octopodes);
     assert_eq!(result, 1656);
-    let mut octopodes = OctopusConfigurationStepper::new(read_grid("input/test/day11.txt")?);
+    let mut octopodes = read_input("input/test/day11.txt")?;
     let result = part2(&mut octopodes);
     assert_eq!(result, 195);
 
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use aoc_lib::ocr;
 use utils::read_file;
 
 #[derive(parse_display::FromStr, PartialEq, Eq, Hash, Debug, Clone, Copy)]
@@ -25,12 +26,6 @@ struct FoldingPaper {
     instructions: Vec<Instruction>,
 }
 
-fn calculate_max_bounds(points: &HashSet<Point>) -> Point {
-    points
-        .iter()
-        .fold(Point(0, 0), |mb, p| Point(p.0.max(mb.0), p.1.max(mb.1)))
-}
-
 impl FoldingPaper {
     fn count_marks(&self) -> usize {
         self.marked_points.len()
@@ -82,19 +77,14 @@ fn part1(paper: &mut FoldingPaper) -> usize {
     paper.count_marks()
 }
 
-fn part2(paper: &mut FoldingPaper) {
+fn part2(paper: &mut FoldingPaper) -> Result<String> {
     paper.do_instructions(&DoInstructions::All);
-    let max_bounds = calculate_max_bounds(&paper.marked_points);
-    for y in 0..=max_bounds.1 {
-        for x in 0..=max_bounds.0 {
-            if paper.marked_points.contains(&Point(x, y)) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
+    let points = paper
+        .marked_points
+        .iter()
+        .map(|&Point(x, y)| (x as i64, y as i64));
+    ocr::read_points(points.clone())
+        .ok_or_else(|| anyhow!("couldn't read the paper:\n{}", ocr::render_points(points)))
 }
 
 fn read_input(path: &str) -> Result<FoldingPaper> {
@@ -119,7 +109,8 @@ fn main() -> Result<()> {
     let result = part1(&mut paper);
     println!("part 1: {}", result);
     let mut paper = read_input("input/day13.txt")?;
-    part2(&mut paper);
+    let result = part2(&mut paper)?;
+    println!("part 2: {}", result);
     Ok(())
 }
 
@@ -129,5 +120,12 @@ fn test() -> Result<()> {
     let result = part1(&mut paper);
     assert_eq!(result, 17);
 
+    // the test instructions fold the paper into a square, not letters
+    let mut paper = read_input("input/test/day13.txt")?;
+    assert!(part2(&mut paper).is_err());
+
+    let mut paper = read_input("input/test/day13_letters.txt")?;
+    assert_eq!(part2(&mut paper)?, "GLOBE");
+
     Ok(())
 }
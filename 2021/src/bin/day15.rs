@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 
-use pathfinding::prelude::dijkstra;
+use aoc_lib::search::dijkstra;
 use utils::read_grid;
 
 type Point = (i16, i16);
@@ -43,26 +43,25 @@ fn adjusted_repeating_map_get(
 fn part1(readings: &RiskReadings) -> u32 {
     let start = (0, 0);
     let end = calculate_max_bounds(readings);
-    let distances = dijkstra(
-        &start,
-        |p| {
+    let path = dijkstra(
+        &|p: &Point| {
             neighbor_points(p)
                 .iter()
                 .filter_map(|a| readings.get(a).map(|r| (*a, *r)))
                 .collect::<Vec<_>>()
         },
+        [start],
         |&p| p == end,
     );
-    distances.unwrap().1
+    path.unwrap().cost
 }
 
 fn part2(readings: &RiskReadings) -> u32 {
     let start = (0, 0);
     let bounds = calculate_max_bounds(readings);
     let end = ((bounds.0 + 1) * 5 - 1, (bounds.1 + 1) * 5 - 1);
-    let distances = dijkstra(
-        &start,
-        |p| {
+    let path = dijkstra(
+        &|p: &Point| {
             neighbor_points(p)
                 .iter()
                 .filter_map(|a| {
@@ -70,9 +69,10 @@ fn part2(readings: &RiskReadings) -> u32 {
                 })
                 .collect::<Vec<_>>()
         },
+        [start],
         |&p| p == end,
     );
-    distances.unwrap().1
+    path.unwrap().cost
 }
 
 fn main() -> Result<()> {
@@ -1,6 +1,6 @@
-use std::{convert::TryFrom, str::FromStr};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use nom::{
     self, bits,
@@ -12,7 +12,7 @@ use nom::{
 };
 use utils::{read_file, InputParseError};
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 enum Packet {
     Literal {
         version: u8,
@@ -46,9 +46,60 @@ impl Packet {
     }
 }
 
+// For following a transmission's evaluation by hand.
+#[allow(dead_code)]
+impl Packet {
+    // The value along with one line per packet, innermost first, showing
+    // what each one worked out to.
+    fn trace(&self) -> (u64, Vec<String>) {
+        let mut lines = Vec::new();
+        let value = self.trace_into(0, &mut lines);
+        (value, lines)
+    }
+
+    fn trace_into(&self, depth: usize, lines: &mut Vec<String>) -> u64 {
+        let value = match self {
+            Packet::Literal { version: _, value } => *value,
+            Packet::Operation {
+                version: _,
+                operator,
+                packets,
+            } => {
+                let values: Vec<u64> = packets
+                    .iter()
+                    .map(|p| p.trace_into(depth + 1, lines))
+                    .collect();
+                operator.apply(&values)
+            }
+        };
+        lines.push(format!("{}{} = {}", "  ".repeat(depth), self, value));
+        value
+    }
+}
+
+// As an S-expression, e.g. (= (+ 1 3) (* 2 2)). Versions are left out.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Literal { version: _, value } => write!(f, "{}", value),
+            Packet::Operation {
+                version: _,
+                operator,
+                packets,
+            } => {
+                write!(f, "({}", operator.symbol())?;
+                for packet in packets {
+                    write!(f, " {}", packet)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 type TheBits<'a> = (&'a [u8], usize);
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 enum Operator {
     Sum,
     Product,
@@ -61,14 +112,43 @@ enum Operator {
 
 impl Operator {
     fn exec(&self, packets: &[Packet]) -> u64 {
+        let values: Vec<u64> = packets.iter().map(|p| p.value()).collect();
+        self.apply(&values)
+    }
+
+    fn apply(&self, values: &[u64]) -> u64 {
+        match self {
+            Operator::Sum => values.iter().sum(),
+            Operator::Product => values.iter().product(),
+            Operator::Minimum => *values.iter().min().unwrap(),
+            Operator::Maximum => *values.iter().max().unwrap(),
+            Operator::GreaterThan => (values[0] > values[1]) as u64,
+            Operator::LessThan => (values[0] < values[1]) as u64,
+            Operator::EqualTo => (values[0] == values[1]) as u64,
+        }
+    }
+
+    fn type_id(&self) -> u8 {
         match self {
-            Operator::Sum => packets.iter().map(|p| p.value()).sum(),
-            Operator::Product => packets.iter().map(|p| p.value()).product(),
-            Operator::Minimum => packets.iter().map(|p| p.value()).min().unwrap(),
-            Operator::Maximum => packets.iter().map(|p| p.value()).max().unwrap(),
-            Operator::GreaterThan => (packets[0].value() > packets[1].value()) as u64,
-            Operator::LessThan => (packets[0].value() < packets[1].value()) as u64,
-            Operator::EqualTo => (packets[0].value() == packets[1].value()) as u64,
+            Operator::Sum => 0,
+            Operator::Product => 1,
+            Operator::Minimum => 2,
+            Operator::Maximum => 3,
+            Operator::GreaterThan => 5,
+            Operator::LessThan => 6,
+            Operator::EqualTo => 7,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Sum => "+",
+            Operator::Product => "*",
+            Operator::Minimum => "min",
+            Operator::Maximum => "max",
+            Operator::GreaterThan => ">",
+            Operator::LessThan => "<",
+            Operator::EqualTo => "=",
         }
     }
 }
@@ -222,6 +302,97 @@ fn calculate_consumed(old: TheBits, new: TheBits) -> u16 {
     ((i_old.len() * 8 - idx_old) - (i_new.len() * 8 - idx_new)) as u16
 }
 
+// How an operator says where its sub-packets end: a total length in bits
+// (length type 0) or a number of packets (length type 1).
+#[allow(dead_code)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum LengthType {
+    Bits,
+    Count,
+}
+
+#[allow(dead_code)]
+#[derive(Default)]
+struct BitWriter {
+    bits: Vec<bool>,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u64, width: usize) -> Result<()> {
+        if width < 64 && value >> width != 0 {
+            bail!("{} doesn't fit in {} bits", value, width);
+        }
+        self.bits
+            .extend((0..width).rev().map(|bit| (value >> bit) & 1 == 1));
+        Ok(())
+    }
+
+    // Padded out with zeros to whole bytes, like a real transmission.
+    fn to_hex(&self) -> String {
+        self.bits
+            .chunks(4)
+            .map(|nibble| {
+                let n = (0..4).fold(0, |n, i| n << 1 | *nibble.get(i).unwrap_or(&false) as u32);
+                std::char::from_digit(n, 16).unwrap().to_ascii_uppercase()
+            })
+            .chain(std::iter::repeat('0'))
+            .take(self.bits.len().div_ceil(8) * 2)
+            .collect()
+    }
+}
+
+fn encode_into(packet: &Packet, length_type: LengthType, out: &mut BitWriter) -> Result<()> {
+    match packet {
+        Packet::Literal { version, value } => {
+            out.push(*version as u64, 3)?;
+            out.push(4, 3)?;
+            let chunks = (0..16)
+                .rev()
+                .map(|i| (value >> (4 * i)) & 0xF)
+                .skip_while(|&c| c == 0)
+                .collect::<Vec<_>>();
+            let chunks = if chunks.is_empty() { vec![0] } else { chunks };
+            for (i, &chunk) in chunks.iter().enumerate() {
+                out.push((i + 1 < chunks.len()) as u64, 1)?;
+                out.push(chunk, 4)?;
+            }
+        }
+        Packet::Operation {
+            version,
+            operator,
+            packets,
+        } => {
+            out.push(*version as u64, 3)?;
+            out.push(operator.type_id() as u64, 3)?;
+            let mut inner = BitWriter::default();
+            for p in packets {
+                encode_into(p, length_type, &mut inner)?;
+            }
+            match length_type {
+                LengthType::Bits => {
+                    out.push(0, 1)?;
+                    out.push(inner.bits.len() as u64, 15)?;
+                }
+                LengthType::Count => {
+                    out.push(1, 1)?;
+                    out.push(packets.len() as u64, 11)?;
+                }
+            }
+            out.bits.extend(inner.bits);
+        }
+    }
+    Ok(())
+}
+
+// Every operator uses `length_type`, so a parsed transmission that mixed
+// them won't come back out as the same hex, just as the same packets.
+#[allow(dead_code)]
+fn encode(packet: &Packet, length_type: LengthType) -> Result<String> {
+    let mut out = BitWriter::default();
+    encode_into(packet, length_type, &mut out)?;
+    Ok(out.to_hex())
+}
+
 fn sum_versions(packet: &Packet) -> u64 {
     match packet {
         Packet::Literal { version, value: _ } => *version as u64,
@@ -377,4 +548,101 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_encode() -> Result<()> {
+        let literal = parse_from_str("D2FE28")?;
+        assert_eq!(encode(&literal, LengthType::Bits)?, "D2FE28");
+        let by_length = parse_from_str("38006F45291200")?;
+        assert_eq!(encode(&by_length, LengthType::Bits)?, "38006F45291200");
+        let by_count = parse_from_str("EE00D40C823060")?;
+        assert_eq!(encode(&by_count, LengthType::Count)?, "EE00D40C823060");
+
+        let too_old = Packet::Literal {
+            version: 8,
+            value: 0,
+        };
+        assert!(encode(&too_old, LengthType::Bits).is_err());
+        Ok(())
+    }
+
+    // A small xorshift, so the round trips cover lots of shapes without
+    // pulling in a crate for random numbers.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, below: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % below
+        }
+
+        fn packet(&mut self, depth: usize) -> Packet {
+            let version = self.next(8) as u8;
+            if depth == 0 || self.next(3) == 0 {
+                let bits = self.next(64) as u32;
+                return Packet::Literal {
+                    version,
+                    value: self.next(u64::MAX) >> (63 - bits.min(63)),
+                };
+            }
+            let operator =
+                Operator::try_from([0, 1, 2, 3, 5, 6, 7][self.next(7) as usize]).unwrap();
+            let count = match operator {
+                Operator::GreaterThan | Operator::LessThan | Operator::EqualTo => 2,
+                _ => 1 + self.next(4) as usize,
+            };
+            Packet::Operation {
+                version,
+                operator,
+                packets: (0..count).map(|_| self.packet(depth - 1)).collect(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trips() -> Result<()> {
+        let mut random = Random(0x2021_0016);
+        for _ in 0..200 {
+            let packet = random.packet(4);
+            for length_type in [LengthType::Bits, LengthType::Count] {
+                let hex = encode(&packet, length_type)?;
+                assert_eq!(parse_from_str(&hex)?, packet, "{}", hex);
+            }
+        }
+        for hex in [
+            "8A004A801A8002F478",
+            "C0015000016115A2E0802F182340",
+            "9C0141080250320F1802104A08",
+        ] {
+            let packet = parse_from_str(hex)?;
+            assert_eq!(
+                parse_from_str(&encode(&packet, LengthType::Count)?)?,
+                packet
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_and_trace() -> Result<()> {
+        let packet = parse_from_str("9C0141080250320F1802104A08")?;
+        assert_eq!(packet.to_string(), "(= (+ 1 3) (* 2 2))");
+        let (value, lines) = packet.trace();
+        assert_eq!(value, 1);
+        assert_eq!(
+            lines,
+            vec![
+                "    1 = 1",
+                "    3 = 3",
+                "  (+ 1 3) = 4",
+                "    2 = 2",
+                "    2 = 2",
+                "  (* 2 2) = 4",
+                "(= (+ 1 3) (* 2 2)) = 1",
+            ]
+        );
+        Ok(())
+    }
 }
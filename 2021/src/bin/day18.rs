@@ -1,10 +1,11 @@
-#![feature(let_else)]
+use std::{fmt, iter::Sum, ops::Add};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use nom::{
     branch::alt,
-    bytes::{complete::take, streaming::tag},
+    bytes::streaming::tag,
+    character::complete::digit1,
     combinator::map_res,
     sequence::{delimited, separated_pair},
     IResult,
@@ -25,12 +26,69 @@ impl std::str::FromStr for DumbNumber {
     }
 }
 
-impl DumbNumber {
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Action {
+    Explode(u8, u8),
+    Split(u8),
+}
+
+// One step of a reduction: what happened, where it happened as a character
+// offset into the number before the step, and the number after it.
+#[cfg(test)]
+#[derive(PartialEq, Debug, Clone)]
+struct Step {
+    action: Action,
+    position: usize,
+    after: DumbNumber,
+}
+
+#[cfg(test)]
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.action {
+            Action::Explode(left, right) => write!(f, "explode [{},{}]", left, right)?,
+            Action::Split(n) => write!(f, "split {}", n)?,
+        }
+        write!(f, " at {}: {}", self.position, self.after)
+    }
+}
+
+impl Add for DumbNumber {
+    type Output = DumbNumber;
+
     fn add(self, other: DumbNumber) -> DumbNumber {
-        let mut result = DumbNumber::Pair(Box::new(self), Box::new(other));
+        let mut result = DumbNumber::pair(self, other);
         result.reduce();
         result
     }
+}
+
+// There's no snailfish zero, so summing nothing gives None rather than a number.
+impl Sum<DumbNumber> for Option<DumbNumber> {
+    fn sum<I: Iterator<Item = DumbNumber>>(iter: I) -> Self {
+        iter.reduce(Add::add)
+    }
+}
+
+impl fmt::Display for DumbNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DumbNumber::Number(n) => write!(f, "{}", n),
+            DumbNumber::Pair(left, right) => write!(f, "[{},{}]", left, right),
+        }
+    }
+}
+
+impl DumbNumber {
+    fn pair(left: DumbNumber, right: DumbNumber) -> DumbNumber {
+        DumbNumber::Pair(Box::new(left), Box::new(right))
+    }
 
     fn leftmost(&mut self) -> &mut u8 {
         match self {
@@ -47,11 +105,16 @@ impl DumbNumber {
     }
 
     fn reduce(&mut self) {
-        loop {
-            if !self.do_explode(0, None, None) && !self.do_split() {
-                break;
-            }
-        }
+        while self.reduce_step().is_some() {}
+    }
+
+    // Explodes if anything can, otherwise splits. Returns what it did and the
+    // path from the top down to where it did it.
+    fn reduce_step(&mut self) -> Option<(Action, Vec<Side>)> {
+        let (action, mut path) = self.do_explode(0, None, None).or_else(|| self.do_split())?;
+        // The paths are built on the way back up, so they start at the bottom.
+        path.reverse();
+        Some((action, path))
     }
 
     fn do_explode(
@@ -59,42 +122,59 @@ impl DumbNumber {
         depth: usize,
         to_the_left: Option<&mut u8>,
         to_the_right: Option<&mut u8>,
-    ) -> bool {
+    ) -> Option<(Action, Vec<Side>)> {
         if depth < 4 {
-            let DumbNumber::Pair(left, right)= self else {
-                return false;
+            let DumbNumber::Pair(left, right) = self else {
+                return None;
             };
-            return left.do_explode(depth + 1, to_the_left, Some(right.leftmost()))
-                || right.do_explode(depth + 1, Some(left.rightmost()), to_the_right);
+            if let Some((action, mut path)) =
+                left.do_explode(depth + 1, to_the_left, Some(right.leftmost()))
+            {
+                path.push(Side::Left);
+                return Some((action, path));
+            }
+            let (action, mut path) =
+                right.do_explode(depth + 1, Some(left.rightmost()), to_the_right)?;
+            path.push(Side::Right);
+            Some((action, path))
         } else {
             match self {
-                DumbNumber::Number(_) => false,
+                DumbNumber::Number(_) => None,
                 DumbNumber::Pair(left, right) => {
                     // leftmost and rightmost only work here because we know it can't be more layers down.
-                    if let Some(l) = to_the_left {
-                        *l += *left.leftmost()
+                    let (l, r) = (*left.leftmost(), *right.rightmost());
+                    if let Some(to_the_left) = to_the_left {
+                        *to_the_left += l
                     }
-                    if let Some(r) = to_the_right {
-                        *r += *right.rightmost()
+                    if let Some(to_the_right) = to_the_right {
+                        *to_the_right += r
                     }
                     *self = DumbNumber::Number(0);
-                    true
+                    Some((Action::Explode(l, r), vec![]))
                 }
             }
         }
     }
 
-    fn do_split(&mut self) -> bool {
+    fn do_split(&mut self) -> Option<(Action, Vec<Side>)> {
         match self {
-            DumbNumber::Pair(left, right) => left.do_split() || right.do_split(),
-            DumbNumber::Number(n) => match n {
-                0..=9 => false,
-                _ => {
-                    *self = DumbNumber::Pair(
-                        Box::from(DumbNumber::Number(*n / 2)),
-                        Box::from(DumbNumber::Number((*n + 1) / 2)),
+            DumbNumber::Pair(left, right) => {
+                if let Some((action, mut path)) = left.do_split() {
+                    path.push(Side::Left);
+                    return Some((action, path));
+                }
+                let (action, mut path) = right.do_split()?;
+                path.push(Side::Right);
+                Some((action, path))
+            }
+            DumbNumber::Number(n) => match *n {
+                0..=9 => None,
+                n => {
+                    *self = DumbNumber::pair(
+                        DumbNumber::Number(n / 2),
+                        DumbNumber::Number(n.div_ceil(2)),
                     );
-                    true
+                    Some((Action::Split(n), vec![]))
                 }
             },
         }
@@ -108,8 +188,44 @@ impl DumbNumber {
     }
 }
 
+#[cfg(test)]
+impl DumbNumber {
+    // The same as adding, but keeping every step of the reduction.
+    fn add_traced(self, other: DumbNumber) -> (DumbNumber, Vec<Step>) {
+        let mut result = DumbNumber::pair(self, other);
+        let steps = result.reduce_traced();
+        (result, steps)
+    }
+
+    fn reduce_traced(&mut self) -> Vec<Step> {
+        let mut steps = vec![];
+        loop {
+            let before = self.clone();
+            let Some((action, path)) = self.reduce_step() else {
+                return steps;
+            };
+            steps.push(Step {
+                action,
+                position: before.offset_of(&path),
+                after: self.clone(),
+            });
+        }
+    }
+
+    // Where the element at the end of `path` starts when printed.
+    fn offset_of(&self, path: &[Side]) -> usize {
+        match (self, path.split_first()) {
+            (DumbNumber::Pair(left, right), Some((side, rest))) => match side {
+                Side::Left => 1 + left.offset_of(rest),
+                Side::Right => 1 + left.to_string().len() + 1 + right.offset_of(rest),
+            },
+            _ => 0,
+        }
+    }
+}
+
 fn parse_actual_number(s: &str) -> IResult<&str, DumbNumber> {
-    map_res(take(1usize), |i: &str| i.parse())(s).map(|(s, n)| (s, DumbNumber::Number(n)))
+    map_res(digit1, |i: &str| i.parse())(s).map(|(s, n)| (s, DumbNumber::Number(n)))
 }
 
 fn dumb_number_element(s: &str) -> IResult<&str, DumbNumber> {
@@ -122,32 +238,26 @@ fn parse_pair(s: &str) -> IResult<&str, DumbNumber> {
         separated_pair(dumb_number_element, tag(","), dumb_number_element),
         tag("]"),
     )(s)?;
-    Ok((s, DumbNumber::Pair(Box::new(left), Box::new(right))))
+    Ok((s, DumbNumber::pair(left, right)))
 }
 
-fn part1(numbers: Vec<DumbNumber>) -> u64 {
-    let mut current_num = None;
-    for num in numbers.into_iter() {
-        current_num = match current_num {
-            None => Some(num),
-            Some(n) => Some(n.add(num)),
-        }
-    }
-    current_num.unwrap().magnitude()
+fn part1(numbers: Vec<DumbNumber>) -> Option<u64> {
+    let total: Option<DumbNumber> = numbers.into_iter().sum();
+    total.map(|n| n.magnitude())
 }
 
 fn part2(numbers: Vec<DumbNumber>) -> u64 {
     numbers
         .iter()
         .cartesian_product(numbers.iter())
-        .map(|(n1, n2)| n1.clone().add(n2.clone()).magnitude())
+        .map(|(n1, n2)| (n1.clone() + n2.clone()).magnitude())
         .max()
         .unwrap()
 }
 
 fn main() -> Result<()> {
     let numbers = read_lines("input/day18.txt")?;
-    let result = part1(numbers);
+    let result = part1(numbers).ok_or_else(|| anyhow!("no numbers to add"))?;
     println!("part 1: {}", result);
 
     let numbers = read_lines("input/day18.txt")?;
@@ -156,15 +266,104 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-#[test]
-fn test() -> Result<()> {
-    let numbers = read_lines("input/test/day18.txt")?;
-    let result = part1(numbers);
-    assert_eq!(result, 4140);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let numbers = read_lines("input/test/day18.txt")?;
-    let result = part2(numbers);
-    assert_eq!(result, 3993);
+    fn number(s: &str) -> DumbNumber {
+        s.parse().unwrap()
+    }
 
-    Ok(())
+    fn sum(numbers: &[&str]) -> String {
+        numbers
+            .iter()
+            .map(|n| number(n))
+            .sum::<Option<_>>()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test() -> Result<()> {
+        let numbers = read_lines("input/test/day18.txt")?;
+        let result = part1(numbers);
+        assert_eq!(result, Some(4140));
+        assert_eq!(part1(vec![]), None);
+
+        let numbers = read_lines("input/test/day18.txt")?;
+        let result = part2(numbers);
+        assert_eq!(result, 3993);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<()> {
+        let numbers: Vec<DumbNumber> = read_lines("input/test/day18.txt")?;
+        let lines: Vec<String> = read_lines("input/test/day18.txt")?;
+        for (n, line) in numbers.iter().zip(&lines) {
+            assert_eq!(&n.to_string(), line);
+        }
+        assert_eq!(number("[10,[3,12]]").to_string(), "[10,[3,12]]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_explode() {
+        for (before, after) in [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            (
+                "[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]",
+                "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]",
+            ),
+        ] {
+            let mut n = number(before);
+            n.reduce_step();
+            assert_eq!(n.to_string(), after);
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let (result, steps) = number("[[[[4,3],4],4],[7,[[8,4],9]]]").add_traced(number("[1,1]"));
+        assert_eq!(result.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        assert_eq!(
+            steps.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec![
+                "explode [4,3] at 4: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+                "explode [8,4] at 16: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+                "split 15 at 13: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+                "split 13 at 22: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+                "explode [6,7] at 22: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sums() -> Result<()> {
+        let numbers = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"];
+        assert_eq!(sum(&numbers[..4]), "[[[[1,1],[2,2]],[3,3]],[4,4]]");
+        assert_eq!(sum(&numbers[..5]), "[[[[3,0],[5,3]],[4,4]],[5,5]]");
+        assert_eq!(sum(&numbers), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+
+        let homework: Vec<DumbNumber> = read_lines("input/test/day18.txt")?;
+        let total: Option<DumbNumber> = homework.into_iter().sum();
+        let total = total.unwrap();
+        assert_eq!(
+            total.to_string(),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_magnitude() {
+        assert_eq!(number("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(
+            number("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(),
+            3488
+        );
+    }
 }
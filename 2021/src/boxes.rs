@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+// An axis-aligned box with inclusive bounds on each of its N axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Box<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+impl<const N: usize> Box<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+        assert!(
+            (0..N).all(|axis| min[axis] <= max[axis]),
+            "box min must not exceed max on any axis"
+        );
+        Self { min, max }
+    }
+
+    pub fn volume(&self) -> i64 {
+        (0..N)
+            .map(|axis| self.max[axis] - self.min[axis] + 1)
+            .product()
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..N).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        (0..N).all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis])
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let mut min = self.min;
+        let mut max = self.max;
+        for axis in 0..N {
+            min[axis] = min[axis].max(other.min[axis]);
+            max[axis] = max[axis].min(other.max[axis]);
+        }
+        Some(Self { min, max })
+    }
+
+    // Splits off the parts of `self` outside `other` one axis at a time, so the
+    // pieces never overlap and there are at most 2N of them.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+        let mut pieces = Vec::new();
+        let mut core = *self;
+        for axis in 0..N {
+            if core.min[axis] < overlap.min[axis] {
+                let mut below = core;
+                below.max[axis] = overlap.min[axis] - 1;
+                pieces.push(below);
+            }
+            if overlap.max[axis] < core.max[axis] {
+                let mut above = core;
+                above.min[axis] = overlap.max[axis] + 1;
+                pieces.push(above);
+            }
+            core.min[axis] = overlap.min[axis];
+            core.max[axis] = overlap.max[axis];
+        }
+        pieces
+    }
+}
+
+// Tracks the volume covered by a sequence of "on" and "off" boxes using signed
+// inclusion-exclusion: every new box cancels out its overlap with everything
+// counted so far, and "on" boxes are then counted themselves.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeAccumulator<const N: usize> {
+    terms: HashMap<Box<N>, i64>,
+}
+
+impl<const N: usize> VolumeAccumulator<N> {
+    pub fn new() -> Self {
+        Self {
+            terms: HashMap::new(),
+        }
+    }
+
+    pub fn apply(&mut self, b: Box<N>, on: bool) {
+        let mut updates: HashMap<Box<N>, i64> = HashMap::new();
+        for (existing, &sign) in &self.terms {
+            if let Some(overlap) = existing.intersection(&b) {
+                *updates.entry(overlap).or_default() -= sign;
+            }
+        }
+        if on {
+            *updates.entry(b).or_default() += 1;
+        }
+        for (b, sign) in updates {
+            let total = self.terms.entry(b).or_default();
+            *total += sign;
+            if *total == 0 {
+                self.terms.remove(&b);
+            }
+        }
+    }
+
+    pub fn add(&mut self, b: Box<N>) {
+        self.apply(b, true);
+    }
+
+    pub fn remove(&mut self, b: Box<N>) {
+        self.apply(b, false);
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.terms.iter().map(|(b, sign)| b.volume() * sign).sum()
+    }
+}
+
+impl<const N: usize> Extend<(Box<N>, bool)> for VolumeAccumulator<N> {
+    fn extend<I: IntoIterator<Item = (Box<N>, bool)>>(&mut self, iter: I) {
+        for (b, on) in iter {
+            self.apply(b, on);
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<(Box<N>, bool)> for VolumeAccumulator<N> {
+    fn from_iter<I: IntoIterator<Item = (Box<N>, bool)>>(iter: I) -> Self {
+        let mut accumulator = Self::new();
+        accumulator.extend(iter);
+        accumulator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangles() {
+        let a = Box::new([0, 0], [9, 9]);
+        let b = Box::new([5, 5], [14, 14]);
+        assert_eq!(a.volume(), 100);
+        assert_eq!(a.intersection(&b), Some(Box::new([5, 5], [9, 9])));
+        assert_eq!(a.intersection(&Box::new([10, 0], [11, 1])), None);
+
+        let pieces = a.subtract(&b);
+        assert_eq!(pieces.iter().map(Box::volume).sum::<i64>(), 75);
+        assert!(pieces
+            .iter()
+            .enumerate()
+            .all(|(i, p)| pieces[i + 1..].iter().all(|q| !p.intersects(q)) && !p.intersects(&b)));
+        assert_eq!(a.subtract(&a), vec![]);
+        assert!(b.contains(&Box::new([5, 5], [9, 9])));
+
+        let accumulator: VolumeAccumulator<2> = vec![(a, true), (b, true)].into_iter().collect();
+        assert_eq!(accumulator.volume(), 175);
+    }
+
+    #[test]
+    fn test_cubes() {
+        // The small reactor reboot example from 2021 day 22.
+        let mut accumulator = VolumeAccumulator::new();
+        accumulator.add(Box::new([10, 10, 10], [12, 12, 12]));
+        accumulator.add(Box::new([11, 11, 11], [13, 13, 13]));
+        accumulator.remove(Box::new([9, 9, 9], [11, 11, 11]));
+        accumulator.add(Box::new([10, 10, 10], [10, 10, 10]));
+        assert_eq!(accumulator.volume(), 39);
+
+        let hollow = Box::new([0, 0, 0], [2, 2, 2]).subtract(&Box::new([1, 1, 1], [1, 1, 1]));
+        assert_eq!(hollow.len(), 6);
+        assert_eq!(hollow.iter().map(Box::volume).sum::<i64>(), 26);
+    }
+
+    #[test]
+    fn test_hypercubes() {
+        let a = Box::new([0; 4], [3; 4]);
+        let b = Box::new([2; 4], [5; 4]);
+        let accumulator: VolumeAccumulator<4> =
+            vec![(a, true), (b, true), (Box::new([2; 4], [3; 4]), false)]
+                .into_iter()
+                .collect();
+        assert_eq!(accumulator.volume(), 256 + 256 - 2 * 16);
+        assert_eq!(
+            a.subtract(&b).iter().map(Box::volume).sum::<i64>(),
+            256 - 16
+        );
+    }
+}
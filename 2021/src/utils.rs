@@ -1,3 +1,6 @@
+pub mod alignment;
+pub mod boxes;
+
 use anyhow::Result;
 use num::Integer;
 use std::collections::HashMap;
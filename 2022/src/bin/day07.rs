@@ -1,11 +1,24 @@
-use anyhow::Result;
-use std::{collections::HashMap, str::FromStr};
+use anyhow::{bail, Result};
+use std::{collections::BTreeMap, fmt, str::FromStr};
 use utils::{read_chunks_delimited, InputParseError};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct File {
-    size: u32,
-    name: String,
+enum Entry {
+    Dir(String),
+    File(String, u32),
+}
+
+impl FromStr for Entry {
+    type Err = InputParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, name) = s.split_once(' ').ok_or(InputParseError)?;
+        if first == "dir" {
+            return Ok(Entry::Dir(name.to_string()));
+        }
+        let size = first.parse().map_err(|_| InputParseError)?;
+        Ok(Entry::File(name.to_string(), size))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,7 +26,7 @@ enum Command {
     CdRoot,
     CdUp,
     Cd(String),
-    Ls(Vec<File>),
+    Ls(Vec<Entry>),
 }
 
 impl FromStr for Command {
@@ -28,92 +41,270 @@ impl FromStr for Command {
         if s == "cd /" {
             return Ok(Command::CdRoot);
         }
-        if s.starts_with("cd ") {
-            let dir = s.split(" ").nth(1).unwrap();
+        if let Some(dir) = s.strip_prefix("cd ") {
             return Ok(Command::Cd(dir.to_string()));
         }
         if s.starts_with("ls") {
-            let internals = s
+            let entries = s
                 .split('\n')
                 .skip(1)
-                .map(|l| l.split_whitespace().collect::<Vec<_>>())
-                .filter_map(|parts| {
-                    // Ignoring dirs for now because they, at the moment, aren't useful.
-                    parts[0]
-                        .parse()
-                        .map(|size| File {
-                            size,
-                            name: parts[1].to_string(),
-                        })
-                        .ok()
-                })
-                .collect();
-            return Ok(Command::Ls(internals));
+                .map(|l| l.parse())
+                .collect::<Result<_, _>>()?;
+            return Ok(Command::Ls(entries));
         }
         println!("Failed to parse: {}", s);
         Err(InputParseError)
     }
 }
 
-fn calculate_fs_size(commands: &[Command]) -> HashMap<String, u32> {
-    let mut file_system_size: HashMap<String, u32> = HashMap::new();
-    let mut current_dir = vec!["/"];
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NodeKind {
+    // Children by name, so the tree prints in a stable order.
+    Dir(BTreeMap<String, usize>),
+    File(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    name: String,
+    parent: Option<usize>,
+    kind: NodeKind,
+}
+
+// Every directory and file the transcript has seen, stored as indexes into
+// `nodes` with the root at 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+const ROOT: usize = 0;
+
+impl FileSystem {
+    fn new() -> Self {
+        FileSystem {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Dir(BTreeMap::new()),
+            }],
+        }
+    }
+
+    fn from_commands(commands: &[Command]) -> Result<Self> {
+        let mut fs = FileSystem::new();
+        let mut current = ROOT;
+        for command in commands {
+            match command {
+                Command::CdRoot => current = ROOT,
+                Command::CdUp => match fs.nodes[current].parent {
+                    Some(parent) => current = parent,
+                    None => bail!("can't cd .. from /"),
+                },
+                Command::Cd(name) => match fs.child(current, name) {
+                    Some(child) if fs.is_dir(child) => current = child,
+                    Some(_) => bail!("{} in {} is a file", name, fs.path(current)),
+                    None => bail!("no directory {} listed in {}", name, fs.path(current)),
+                },
+                Command::Ls(entries) => {
+                    for entry in entries {
+                        fs.add(current, entry)?;
+                    }
+                }
+            }
+        }
+        Ok(fs)
+    }
+
+    fn child(&self, dir: usize, name: &str) -> Option<usize> {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir(children) => children.get(name).copied(),
+            NodeKind::File(_) => None,
+        }
+    }
+
+    fn is_dir(&self, node: usize) -> bool {
+        matches!(self.nodes[node].kind, NodeKind::Dir(_))
+    }
+
+    fn path(&self, node: usize) -> String {
+        let mut names = Vec::new();
+        let mut node = node;
+        while let Some(parent) = self.nodes[node].parent {
+            names.push(self.nodes[node].name.as_str());
+            node = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
 
-    for command in commands {
-        match command {
-            Command::CdRoot => current_dir = vec!["/"],
-            Command::CdUp => {
-                current_dir.pop();
+    // Listing the same directory twice doesn't add anything new.
+    fn add(&mut self, dir: usize, entry: &Entry) -> Result<()> {
+        let (name, kind) = match entry {
+            Entry::Dir(name) => (name, NodeKind::Dir(BTreeMap::new())),
+            Entry::File(name, size) => (name, NodeKind::File(*size)),
+        };
+        if let Some(existing) = self.child(dir, name) {
+            if self.is_dir(existing) != matches!(kind, NodeKind::Dir(_)) {
+                bail!(
+                    "{} in {} changed type between listings",
+                    name,
+                    self.path(dir)
+                );
             }
-            Command::Cd(dir) => current_dir.push(dir),
-            Command::Ls(internals) => {
-                let sum = internals.iter().map(|f| f.size).sum::<u32>();
-                let mut key_dir = "".to_string();
-                // For the current dir and its parent, add the sum of the files.
-                for &dir in current_dir.iter() {
-                    key_dir.push_str(dir);
-                    *file_system_size.entry(key_dir.clone()).or_insert(0) += sum;
+            return Ok(());
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.clone(),
+            parent: Some(dir),
+            kind,
+        });
+        if let NodeKind::Dir(children) = &mut self.nodes[dir].kind {
+            children.insert(name.clone(), index);
+        }
+        Ok(())
+    }
+
+    fn size(&self, node: usize) -> u32 {
+        match &self.nodes[node].kind {
+            NodeKind::Dir(children) => children.values().map(|&c| self.size(c)).sum(),
+            NodeKind::File(size) => *size,
+        }
+    }
+
+    fn dirs(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(move |&n| self.is_dir(n))
+    }
+
+    // The smallest directory that frees up enough space for `needed` once
+    // deleted, along with its size.
+    fn smallest_deletable(&self, total: u32, needed: u32) -> Option<(usize, u32)> {
+        let free = total.checked_sub(self.size(ROOT))?;
+        let to_free = needed.saturating_sub(free);
+        self.dirs()
+            .map(|d| (d, self.size(d)))
+            .filter(|&(_, size)| size >= to_free)
+            .min_by_key(|&(_, size)| size)
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter<'_>, node: usize, depth: usize) -> fmt::Result {
+        let Node { name, kind, .. } = &self.nodes[node];
+        let indent = "  ".repeat(depth);
+        match kind {
+            NodeKind::Dir(children) => {
+                writeln!(f, "{}- {} (dir)", indent, name)?;
+                for &child in children.values() {
+                    self.fmt_node(f, child, depth + 1)?;
                 }
+                Ok(())
             }
+            NodeKind::File(size) => writeln!(f, "{}- {} (file, size={})", indent, name, size),
         }
     }
-    file_system_size
 }
 
-fn part1(commands: &[Command]) -> u32 {
-    calculate_fs_size(commands)
-        .values()
-        .filter(|&&s| s <= 100000)
-        .sum()
+// The same layout as the listing in the puzzle description.
+impl fmt::Display for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_node(f, ROOT, 0)
+    }
+}
+
+fn part1(fs: &FileSystem) -> u32 {
+    fs.dirs().map(|d| fs.size(d)).filter(|&s| s <= 100000).sum()
 }
 
-fn part2(commands: &[Command]) -> Option<u32> {
-    let total_size = 70000000;
-    let needed_size = 30000000;
-    let fs_size = calculate_fs_size(commands);
-    let used_size = fs_size.values().max().unwrap();
-    let free_size = total_size - used_size;
-    let to_free = needed_size - free_size;
-    fs_size.values().filter(|&&s| s >= to_free).min().copied()
+fn part2(fs: &FileSystem) -> Option<u32> {
+    fs.smallest_deletable(70000000, 30000000)
+        .map(|(_, size)| size)
 }
 
 fn main() -> Result<()> {
-    let commands = read_chunks_delimited("input/day07.txt", "\n$ ")?;
-    let result = part1(&commands);
+    let commands: Vec<Command> = read_chunks_delimited("input/day07.txt", "\n$ ")?;
+    let fs = FileSystem::from_commands(&commands)?;
+    let result = part1(&fs);
     println!("part 1: {}", result);
-    let result = part2(&commands);
+    let result = part2(&fs);
     println!("part 2: {:?}", result);
     Ok(())
 }
 
-#[test]
-fn test() -> Result<()> {
-    let commands: Vec<Command> = read_chunks_delimited("input/test/day07.txt", "\n$ ")?;
-    let result = part1(&commands);
-    assert_eq!(result, 95437);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(transcript: &str) -> Result<FileSystem> {
+        let commands = transcript
+            .split("\n$ ")
+            .map(|c| c.parse())
+            .collect::<Result<Vec<Command>, _>>()
+            .map_err(|_| anyhow::anyhow!("bad transcript"))?;
+        FileSystem::from_commands(&commands)
+    }
 
-    let result = part2(&commands);
-    assert_eq!(result, Some(24933642));
+    #[test]
+    fn test() -> Result<()> {
+        let commands: Vec<Command> = read_chunks_delimited("input/test/day07.txt", "\n$ ")?;
+        let fs = FileSystem::from_commands(&commands)?;
+        let result = part1(&fs);
+        assert_eq!(result, 95437);
 
-    Ok(())
+        let result = part2(&fs);
+        assert_eq!(result, Some(24933642));
+
+        let (dir, _) = fs.smallest_deletable(70000000, 30000000).unwrap();
+        assert_eq!(fs.nodes[dir].name, "d");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree() -> Result<()> {
+        let commands: Vec<Command> = read_chunks_delimited("input/test/day07.txt", "\n$ ")?;
+        let fs = FileSystem::from_commands(&commands)?;
+        let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+        assert_eq!(fs.to_string(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_paths_dont_collide() -> Result<()> {
+        // "/" + "a" + "b" and "/" + "ab" used to add up under the same key
+        let fs = build("$ cd /\n$ ls\ndir a\ndir ab\n$ cd a\n$ ls\ndir b\n$ cd b\n$ ls\n10 x\n$ cd /\n$ cd ab\n$ ls\n20 y")?;
+        let sizes: Vec<_> = fs.dirs().map(|d| (fs.path(d), fs.size(d))).collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("/".to_string(), 30),
+                ("/a".to_string(), 10),
+                ("/ab".to_string(), 20),
+                ("/a/b".to_string(), 10),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_cds() {
+        assert!(build("$ cd /\n$ cd a").is_err());
+        assert!(build("$ cd /\n$ ls\n5 a\n$ cd a").is_err());
+        assert!(build("$ cd /\n$ cd ..").is_err());
+        assert!(build("$ cd /\n$ ls\ndir a\n$ cd a\n$ cd ..\n$ cd a").is_ok());
+    }
 }
@@ -1,6 +1,7 @@
 use std::iter;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use aoc_lib::ocr;
 use itertools::Itertools;
 use parse_display::FromStr;
 use utils::read_lines;
@@ -40,7 +41,7 @@ fn part1(instructions: &[Instr]) -> i32 {
         .sum()
 }
 
-fn part2(instructions: &[Instr]) -> String {
+fn render(instructions: &[Instr]) -> String {
     // Rust's scan is not consistent with my understanding of scan.
     // In Haskell, Kotlin, etc. scan will return the initial value
     // but Rust's scan will not. So we have to add it manually.
@@ -70,12 +71,17 @@ fn part2(instructions: &[Instr]) -> String {
         .join("\n")
 }
 
+fn part2(instructions: &[Instr]) -> Result<String> {
+    let screen = render(instructions);
+    ocr::read_screen(&screen).ok_or_else(|| anyhow!("couldn't read the screen:\n{}", screen))
+}
+
 fn main() -> Result<()> {
     let instructions = read_lines("input/day10.txt")?;
     let result = part1(&instructions);
     println!("part 1: {}", result);
     let result = part2(&instructions);
-    println!("part 2: \n{}", result);
+    println!("part 2: {}", result?);
     Ok(())
 }
 
@@ -85,8 +91,7 @@ fn test() -> Result<()> {
     let result = part1(&instructions);
     assert_eq!(result, 13140);
 
-    let result = part2(&instructions);
-    println!("{}", result);
+    let result = render(&instructions);
     assert_eq!(
         result,
         // I swear there's some funky formatting thing I can do here
@@ -98,6 +103,11 @@ fn test() -> Result<()> {
 ######......######......######......####
 #######.......#######.......#######....."
     );
+    // the test program doesn't draw letters
+    assert!(part2(&instructions).is_err());
+
+    let instructions = read_lines("input/test/day10_letters.txt")?;
+    assert_eq!(part2(&instructions)?, "FRESHCUP");
 
     Ok(())
 }
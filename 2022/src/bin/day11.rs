@@ -1,51 +1,167 @@
-use std::{
-    collections::{HashMap, VecDeque},
-    str::FromStr,
-};
+use std::{collections::VecDeque, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use aoc_lib::cycles::brent_within;
 use itertools::Itertools;
+use num::integer::lcm;
 use parse_display::FromStr;
 use utils::{read_chunks, InputParseError};
 
-#[derive(FromStr, Debug, Clone, Copy, PartialEq, Eq)]
-enum OperationType {
-    #[display("*")]
-    Multiply,
-    #[display("+")]
-    Add,
-}
-
-#[derive(FromStr, Debug, Clone, Copy, PartialEq, Eq)]
-enum OperationInput {
-    #[display("old")]
+// Arithmetic over `old`, with the usual precedence and brackets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
     Old,
-    #[from_str(regex = "(?P<0>[0-9]+)")]
     Value(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
 }
 
-#[derive(FromStr, Debug, Clone, Copy, PartialEq, Eq)]
-#[display("Operation: new = {left} {operation} {right}")]
-struct Operation {
-    left: OperationInput,
-    operation: OperationType,
-    right: OperationInput,
-}
+impl Expr {
+    // Worry levels can't go below zero or past a u64, so either is an error.
+    fn eval(&self, old: u64) -> Result<u64> {
+        Ok(match self {
+            Expr::Old => old,
+            Expr::Value(v) => *v,
+            Expr::Add(a, b) => {
+                let (a, b) = (a.eval(old)?, b.eval(old)?);
+                a.checked_add(b)
+                    .ok_or_else(|| anyhow!("{} + {} overflows", a, b))?
+            }
+            Expr::Sub(a, b) => {
+                let (a, b) = (a.eval(old)?, b.eval(old)?);
+                a.checked_sub(b)
+                    .ok_or_else(|| anyhow!("{} - {} is below zero", a, b))?
+            }
+            Expr::Mul(a, b) => {
+                let (a, b) = (a.eval(old)?, b.eval(old)?);
+                a.checked_mul(b)
+                    .ok_or_else(|| anyhow!("{} * {} overflows", a, b))?
+            }
+        })
+    }
 
-impl Operation {
-    fn apply(&self, old: u64) -> u64 {
-        let left = match self.left {
-            OperationInput::Old => old,
-            OperationInput::Value(v) => v,
+    // The same, modulo `modulus`, without overflowing along the way.
+    fn eval_mod(&self, old: u64, modulus: u64) -> u64 {
+        let m = modulus as u128;
+        let result = match self {
+            Expr::Old => old as u128 % m,
+            Expr::Value(v) => *v as u128 % m,
+            Expr::Add(a, b) => a.eval_mod(old, modulus) as u128 + b.eval_mod(old, modulus) as u128,
+            Expr::Sub(a, b) => {
+                a.eval_mod(old, modulus) as u128 + m - b.eval_mod(old, modulus) as u128
+            }
+            Expr::Mul(a, b) => a.eval_mod(old, modulus) as u128 * b.eval_mod(old, modulus) as u128,
         };
-        let right = match self.right {
-            OperationInput::Old => old,
-            OperationInput::Value(v) => v,
+        (result % m) as u64
+    }
+}
+
+struct ExprParser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.input.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+        self.input.get(self.position).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), InputParseError> {
+        if self.peek() != Some(c) {
+            return Err(InputParseError);
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn sum(&mut self) -> Result<Expr, InputParseError> {
+        let mut expr = self.product()?;
+        while let Some(op @ (b'+' | b'-')) = self.peek() {
+            self.position += 1;
+            let (a, b) = (Box::new(expr), Box::new(self.product()?));
+            expr = match op {
+                b'+' => Expr::Add(a, b),
+                _ => Expr::Sub(a, b),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, InputParseError> {
+        let mut expr = self.factor()?;
+        while self.peek() == Some(b'*') {
+            self.position += 1;
+            expr = Expr::Mul(Box::new(expr), Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr, InputParseError> {
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let expr = self.sum()?;
+                self.expect(b')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.position;
+                while self
+                    .input
+                    .get(self.position)
+                    .is_some_and(|c| c.is_ascii_digit())
+                {
+                    self.position += 1;
+                }
+                std::str::from_utf8(&self.input[start..self.position])
+                    .unwrap()
+                    .parse()
+                    .map(Expr::Value)
+                    .map_err(|_| InputParseError)
+            }
+            _ if self.input[self.position..].starts_with(b"old") => {
+                self.position += 3;
+                Ok(Expr::Old)
+            }
+            _ => Err(InputParseError),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = InputParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExprParser {
+            input: s.as_bytes(),
+            position: 0,
         };
-        match self.operation {
-            OperationType::Multiply => left * right,
-            OperationType::Add => left + right,
+        let expr = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(InputParseError);
         }
+        Ok(expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Operation {
+    new: Expr,
+}
+
+impl FromStr for Operation {
+    type Err = InputParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let new = s
+            .strip_prefix("Operation: new = ")
+            .ok_or(InputParseError)?
+            .parse()?;
+        Ok(Operation { new })
     }
 }
 
@@ -126,59 +242,278 @@ impl FromStr for Monkey {
     }
 }
 
-fn run(monkeys: &mut [Monkey], iterations: u16, reducer: &dyn Fn(u64) -> u64) -> u64 {
-    let mut to_push = monkeys
-        .iter()
-        .map(|m| (m.id, VecDeque::<u64>::new()))
-        .collect::<HashMap<_, _>>();
-    for _ in 0..iterations {
-        for monkey in monkeys.iter_mut() {
-            monkey.items.append(to_push.get_mut(&monkey.id).unwrap());
-            while let Some(item) = monkey.inspect() {
-                let new_worry_level = reducer(monkey.operation.apply(item));
-                let target_monkey = monkey.test(new_worry_level);
-                to_push
-                    .get_mut(&target_monkey)
-                    .unwrap()
-                    .push_back(new_worry_level);
+// How worry levels are kept in check after each inspection: divided down,
+// or reduced modulo the LCM of every monkey's divisor, which keeps every
+// test's answer the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relief {
+    DivideBy(u64),
+    Modulo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Troop {
+    monkeys: Vec<Monkey>,
+    relief: Relief,
+    modulus: u64,
+}
+
+impl Troop {
+    fn new(monkeys: Vec<Monkey>, relief: Relief) -> Self {
+        assert!(
+            monkeys.iter().enumerate().all(|(i, m)| m.id as usize == i),
+            "monkeys must be listed in order"
+        );
+        let modulus = monkeys.iter().map(|m| m.test.divisor).fold(1, lcm);
+        Troop {
+            monkeys,
+            relief,
+            modulus,
+        }
+    }
+
+    fn worry(&self, monkey: usize, old: u64) -> Result<u64> {
+        let operation = &self.monkeys[monkey].operation;
+        Ok(match self.relief {
+            Relief::DivideBy(d) => operation.new.eval(old)? / d,
+            Relief::Modulo => operation.new.eval_mod(old, self.modulus),
+        })
+    }
+
+    // Plays one round, returning how many items each monkey inspected in it.
+    fn round(&mut self) -> Result<Vec<u64>> {
+        let mut inspections = vec![0; self.monkeys.len()];
+        for (i, inspected) in inspections.iter_mut().enumerate() {
+            while let Some(item) = self.monkeys[i].inspect() {
+                *inspected += 1;
+                let new_worry_level = self.worry(i, item)?;
+                let target_monkey = self.monkeys[i].test(new_worry_level) as usize;
+                self.monkeys[target_monkey].items.push_back(new_worry_level);
+            }
+        }
+        Ok(inspections)
+    }
+
+    // The inspections in each round, one entry per round.
+    fn run(&mut self, rounds: usize) -> Result<Vec<Vec<u64>>> {
+        (0..rounds).map(|_| self.round()).collect()
+    }
+
+    fn inspection_counts(&self) -> Vec<u64> {
+        self.monkeys.iter().map(|m| m.inspection_count).collect()
+    }
+
+    // Follows a single item through one round, starting with `monkey`, and
+    // returns where it starts the next round. Items never affect each other,
+    // so each one can be followed on its own. `inspected` hears about every
+    // monkey that handles it.
+    fn item_round<F: FnMut(usize)>(
+        &self,
+        (mut monkey, mut worry): (usize, u64),
+        mut inspected: F,
+    ) -> Result<(usize, u64)> {
+        loop {
+            inspected(monkey);
+            worry = self.worry(monkey, worry)?;
+            let target = self.monkeys[monkey].test(worry) as usize;
+            // Throwing back to an earlier monkey waits for the next round.
+            if target <= monkey {
+                return Ok((target, worry));
+            }
+            monkey = target;
+        }
+    }
+
+    fn item_rounds(
+        &self,
+        mut item: (usize, u64),
+        rounds: u64,
+        counts: &mut [u64],
+    ) -> Result<(usize, u64)> {
+        for _ in 0..rounds {
+            item = self.item_round(item, |monkey| counts[monkey] += 1)?;
+        }
+        Ok(item)
+    }
+
+    // How many times each monkey inspects something over `rounds` rounds,
+    // without playing them all when the items' journeys loop.
+    fn inspections_after(&self, rounds: u64) -> Result<Vec<u64>> {
+        let mut counts = vec![0; self.monkeys.len()];
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            for &worry in &monkey.items {
+                let start = (i, worry);
+                // An item whose worry level goes out of range ends up as None
+                // for good, and playing the rounds out below hits the error.
+                let next = |item: &Option<(usize, u64)>| {
+                    item.and_then(|item| self.item_round(item, |_| {}).ok())
+                };
+                match brent_within(Some(start), next, rounds as usize) {
+                    Some(cycle) if (cycle.start as u64) < rounds => {
+                        let (before, length) = (cycle.start as u64, cycle.length as u64);
+                        let item = self.item_rounds(start, before, &mut counts)?;
+                        let mut cycle = vec![0; counts.len()];
+                        self.item_rounds(item, length, &mut cycle)?;
+                        let remaining = rounds - before;
+                        for (count, c) in counts.iter_mut().zip(&cycle) {
+                            *count += remaining / length * c;
+                        }
+                        self.item_rounds(item, remaining % length, &mut counts)?;
+                    }
+                    _ => {
+                        self.item_rounds(start, rounds, &mut counts)?;
+                    }
+                }
             }
         }
+        Ok(counts)
     }
-    monkeys
-        .iter()
-        .map(|m| m.inspection_count)
-        .sorted()
-        .rev()
-        .take(2)
-        .product()
 }
 
-fn part1(monkeys: &mut [Monkey]) -> u64 {
-    run(monkeys, 20, &|x| x / 3)
+fn monkey_business(inspection_counts: &[u64]) -> u64 {
+    inspection_counts.iter().sorted().rev().take(2).product()
 }
 
-fn part2(monkeys: &mut [Monkey]) -> u64 {
-    let divisor_products = monkeys.iter().map(|m| m.test.divisor).product::<u64>();
-    run(monkeys, 10000, &|x| x % divisor_products)
+fn part1(monkeys: &[Monkey]) -> Result<u64> {
+    let mut troop = Troop::new(monkeys.to_vec(), Relief::DivideBy(3));
+    troop.run(20)?;
+    Ok(monkey_business(&troop.inspection_counts()))
+}
+
+fn part2(monkeys: &[Monkey]) -> Result<u64> {
+    let troop = Troop::new(monkeys.to_vec(), Relief::Modulo);
+    Ok(monkey_business(&troop.inspections_after(10000)?))
 }
 
 fn main() -> Result<()> {
     let monkeys = read_chunks("input/day11.txt")?;
-    let result = part1(&mut monkeys.clone());
+    let result = part1(&monkeys)?;
     println!("part 1: {}", result);
-    let result = part2(&mut monkeys.clone());
+    let result = part2(&monkeys)?;
     println!("part 2: {}", result);
     Ok(())
 }
 
-#[test]
-fn test() -> Result<()> {
-    let monkeys = read_chunks("input/test/day11.txt")?;
-    let result = part1(&mut monkeys.clone());
-    assert_eq!(result, 10605);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let result = part2(&mut monkeys.clone());
-    assert_eq!(result, 2713310158);
+    #[test]
+    fn test() -> Result<()> {
+        let monkeys: Vec<Monkey> = read_chunks("input/test/day11.txt")?;
+        let result = part1(&monkeys)?;
+        assert_eq!(result, 10605);
 
-    Ok(())
+        let result = part2(&monkeys)?;
+        assert_eq!(result, 2713310158);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expressions() -> Result<()> {
+        let expr: Expr = "old * (old + 3) + 2 * old".parse()?;
+        assert_eq!(expr.eval(5)?, 50);
+        assert_eq!(expr.eval_mod(5, 7), 1);
+        let expr: Expr = "old - 2".parse()?;
+        assert_eq!(expr.eval(5)?, 3);
+        assert_eq!(expr.eval_mod(5, 7), 3);
+        assert_eq!(expr.eval_mod(1, 7), 6);
+        assert!(expr.eval(1).is_err());
+        let expr: Expr = "old * 3 - (old + 1) - 2".parse()?;
+        assert_eq!(expr.eval(10)?, 17);
+        assert_eq!(expr.eval_mod(10, 7), 3);
+        assert!("old * old".parse::<Expr>()?.eval(1 << 32).is_err());
+        // big enough that squaring it overflows a u64
+        let modulus = (1 << 40) + 15;
+        assert_eq!(
+            "old * old".parse::<Expr>()?.eval_mod(modulus - 2, modulus),
+            4
+        );
+        assert!("old +".parse::<Expr>().is_err());
+        assert!("(old".parse::<Expr>().is_err());
+        assert!("old / 2".parse::<Expr>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rounds() -> Result<()> {
+        let monkeys: Vec<Monkey> = read_chunks("input/test/day11.txt")?;
+        let mut troop = Troop::new(monkeys.clone(), Relief::Modulo);
+        assert_eq!(troop.modulus, 23 * 19 * 13 * 17);
+        let rounds = troop.run(1000)?;
+        assert_eq!(rounds[0], vec![2, 4, 3, 6]);
+        let total = |n: usize| {
+            rounds[..n].iter().fold(vec![0; 4], |total, round| {
+                total.iter().zip(round).map(|(a, b)| a + b).collect()
+            })
+        };
+        assert_eq!(total(20), vec![99, 97, 8, 103]);
+        assert_eq!(total(1000), vec![5204, 4792, 199, 5192]);
+        assert_eq!(troop.inspection_counts(), total(1000));
+
+        let troop = Troop::new(monkeys, Relief::Modulo);
+        for n in [1, 20, 1000] {
+            assert_eq!(troop.inspections_after(n as u64)?, total(n));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_many_rounds() -> Result<()> {
+        let monkeys: Vec<Monkey> = read_chunks("input/test/day11.txt")?;
+        let troop = Troop::new(monkeys, Relief::Modulo);
+        let mut simulated = troop.clone();
+        simulated.run(10000)?;
+        assert_eq!(
+            troop.inspections_after(10000)?,
+            simulated.inspection_counts()
+        );
+        // far more rounds than could be played one by one, with each of the
+        // ten items inspected at least once a round
+        let counts = troop.inspections_after(1_000_000_000_000)?;
+        assert!(counts.iter().sum::<u64>() >= 10 * 1_000_000_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_out_of_range() -> Result<()> {
+        let monkey: Monkey = "Monkey 0:\n\
+                              Starting items: 5\n\
+                              Operation: new = old - 10\n\
+                              Test: divisible by 2\n\
+                              If true: throw to monkey 0\n\
+                              If false: throw to monkey 0"
+            .parse()?;
+        let mut troop = Troop::new(vec![monkey.clone()], Relief::DivideBy(3));
+        assert!(troop.run(1).is_err());
+        let troop = Troop::new(vec![monkey], Relief::DivideBy(1));
+        assert!(troop.inspections_after(5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_many_monkeys() -> Result<()> {
+        // more monkeys than bits in a u64, each passing the item to the next
+        let monkeys = (0..70)
+            .map(|i| {
+                format!(
+                    "Monkey {}:\n\
+                     Starting items: {}\n\
+                     Operation: new = old + 1\n\
+                     Test: divisible by 2\n\
+                     If true: throw to monkey {}\n\
+                     If false: throw to monkey {}",
+                    i,
+                    if i == 0 { "5" } else { "" },
+                    (i + 1) % 70,
+                    (i + 1) % 70
+                )
+                .parse()
+            })
+            .collect::<Result<Vec<Monkey>, _>>()?;
+        let troop = Troop::new(monkeys, Relief::Modulo);
+        assert_eq!(troop.inspections_after(3)?, vec![3; 70]);
+        Ok(())
+    }
 }
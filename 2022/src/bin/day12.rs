@@ -1,6 +1,6 @@
 use anyhow::Result;
+use aoc_lib::search::bfs;
 use parse_display::FromStr;
-use pathfinding::prelude::bfs;
 use utils::{read_grid, Grid};
 
 #[derive(FromStr, PartialEq, Eq, Debug, Clone, Copy)]
@@ -23,50 +23,34 @@ impl MapPoint {
     }
 }
 
-fn run_2(
-    map: &Grid<i16, MapPoint>,
-    start: MapPoint,
-    end: &dyn Fn(MapPoint) -> bool,
-    test_valid: &dyn Fn(u32, u32) -> bool,
-) -> Option<u32> {
-    let start = map
-        .iter()
-        .find(|(_, p)| *p == &start)
-        .map(|(p, _)| p)
-        .unwrap();
+fn climb(map: &Grid<i16, MapPoint>, starts: &dyn Fn(MapPoint) -> bool) -> Option<u32> {
+    let starts = map.iter().filter(|(_, p)| starts(**p)).map(|(p, _)| *p);
     let path = bfs(
-        start,
-        |p| {
-            let height = map.get(p).map(|h| h.height()).unwrap_or(u32::MIN);
-            let res = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+        &|p: &(i16, i16)| {
+            let height = map[p].height();
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
                 .iter()
                 .map(|(x, y)| (x + p.0, y + p.1))
                 .filter(|n| {
                     map.get(n)
-                        .map(|nh| test_valid(nh.height(), height))
+                        .map(|nh| nh.height() <= height + 1)
                         .unwrap_or(false)
                 })
-                .collect::<Vec<_>>();
-            res
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
         },
-        |p| end(map.get(p).copied().unwrap_or(MapPoint::Point(' '))),
+        starts,
+        |p| map[p] == MapPoint::End,
     );
-    path.map(|p| p.len() as u32 - 1)
+    path.map(|p| p.cost as u32)
 }
 
 fn part1(map: &Grid<i16, MapPoint>) -> Option<u32> {
-    run_2(map, MapPoint::Start, &|v| v == MapPoint::End, &|a, b| {
-        a <= b + 1
-    })
+    climb(map, &|p| p == MapPoint::Start)
 }
 
 fn part2(map: &Grid<i16, MapPoint>) -> Option<u32> {
-    run_2(
-        map,
-        MapPoint::End,
-        &|v| v == MapPoint::Start || v == MapPoint::Point('a'),
-        &|a, b| a >= b - 1,
-    )
+    climb(map, &|p| p.height() == 'a' as u32)
 }
 
 fn main() -> Result<()> {
@@ -1,18 +1,11 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt};
 
 use anyhow::Result;
 use itertools::{EitherOrBoth, Itertools};
-use nom::{
-    branch::alt,
-    bytes::complete::take_while,
-    character::complete::char,
-    combinator::map_res,
-    combinator::{map, opt},
-    sequence::delimited,
-    IResult,
-};
-
-#[derive(Debug, Clone, PartialEq, Ord, Eq)]
+
+// Ordered with the puzzle's rule that a number compares like a list holding
+// just that number, so 1, [1] and [[1]] are all equal.
+#[derive(Debug, Clone)]
 enum Packet {
     Number(u16),
     List(Box<Vec<Packet>>),
@@ -27,85 +20,185 @@ impl Packet {
     }
 }
 
-impl PartialOrd<Packet> for Packet {
-    fn partial_cmp(&self, other: &Packet) -> Option<Ordering> {
+impl Ord for Packet {
+    fn cmp(&self, other: &Packet) -> Ordering {
         match (self, other) {
-            (Packet::Number(a), Packet::Number(b)) => a.partial_cmp(b),
+            (Packet::Number(a), Packet::Number(b)) => a.cmp(b),
             (Packet::List(a), Packet::List(b)) => a
                 .iter()
                 .zip_longest(b.iter())
                 .map(|zip| match zip {
-                    EitherOrBoth::Both(a, b) => a.partial_cmp(b),
-                    EitherOrBoth::Left(_) => Some(Ordering::Greater), // Right side ran out
-                    EitherOrBoth::Right(_) => Some(Ordering::Less),   // Left side ran out
+                    EitherOrBoth::Both(a, b) => a.cmp(b),
+                    EitherOrBoth::Left(_) => Ordering::Greater, // Right side ran out
+                    EitherOrBoth::Right(_) => Ordering::Less,   // Left side ran out
                 })
-                .find(|cmp| cmp != &Some(Ordering::Equal))
-                .unwrap_or(Some(Ordering::Equal)),
-            (Packet::Number(_), Packet::List(_)) => self.listed().partial_cmp(other),
-            (Packet::List(_), Packet::Number(_)) => self.partial_cmp(&other.listed()),
+                .find(|cmp| cmp != &Ordering::Equal)
+                .unwrap_or(Ordering::Equal),
+            (Packet::Number(_), Packet::List(_)) => self.listed().cmp(other),
+            (Packet::List(_), Packet::Number(_)) => self.cmp(&other.listed()),
         }
     }
 }
 
-fn parse_list(s: &str) -> IResult<&str, Vec<Packet>> {
-    delimited(char('['), parse_many, char(']'))(s)
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Packet) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-fn parse_list_packet(s: &str) -> IResult<&str, Packet> {
-    map(parse_list, |packets| Packet::List(Box::new(packets)))(s)
+impl PartialEq for Packet {
+    fn eq(&self, other: &Packet) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
-fn parse_many(s: &str) -> IResult<&str, Vec<Packet>> {
-    let (s, first) = opt(parse_packet)(s)?;
-    let Some(first) = first else {
-        return Ok((s, vec![]));
-    };
-    let (s, rest) = opt(delimited(char(','), parse_many, opt(char(','))))(s)?;
-    let mut packets = vec![first];
-    if let Some(mut rest) = rest {
-        packets.append(&mut rest);
+impl Eq for Packet {}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Number(n) => write!(f, "{}", n),
+            Packet::List(packets) => write!(f, "[{}]", packets.iter().join(",")),
+        }
     }
-    Ok((s, packets))
 }
 
-fn is_char_digit(c: char) -> bool {
-    c.is_ascii_digit()
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseErrorKind {
+    Unclosed { opened_at: usize },
+    UnexpectedClose,
+    Unexpected(char),
+    BadNumber,
+    EndOfInput,
+    TrailingInput,
 }
 
-fn parse_integer(input: &str) -> IResult<&str, u16> {
-    map_res(take_while(is_char_digit), str::parse)(input)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParseError {
+    position: usize,
+    kind: ParseErrorKind,
 }
 
-fn parse_number_packet(s: &str) -> IResult<&str, Packet> {
-    map(parse_integer, Packet::Number)(s)
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Unclosed { opened_at } => {
+                write!(f, "'[' at {} isn't closed by {}", opened_at, self.position)
+            }
+            ParseErrorKind::UnexpectedClose => write!(f, "unmatched ']' at {}", self.position),
+            ParseErrorKind::Unexpected(c) => write!(f, "unexpected {:?} at {}", c, self.position),
+            ParseErrorKind::BadNumber => write!(f, "number too big at {}", self.position),
+            ParseErrorKind::EndOfInput => write!(f, "packet ends early at {}", self.position),
+            ParseErrorKind::TrailingInput => {
+                write!(f, "unexpected text after the packet at {}", self.position)
+            }
+        }
+    }
 }
 
-fn parse_packet(s: &str) -> IResult<&str, Packet> {
-    alt((parse_list_packet, parse_number_packet))(s)
+impl std::error::Error for ParseError {}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
 }
 
-impl std::str::FromStr for Packet {
-    type Err = anyhow::Error;
+impl<'a> Parser<'a> {
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            position: self.position,
+            kind,
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self> {
-        parse_packet(s)
-            .map_err(|e| {
-                println!("Error parsing packet: {:?}", e);
-                anyhow::anyhow!("Failed to parse packet")
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn packet(&mut self) -> Result<Packet, ParseError> {
+        match self.peek() {
+            Some(b'[') => self.list(),
+            Some(c) if c.is_ascii_digit() => self.number(),
+            Some(b']') => Err(self.error(ParseErrorKind::UnexpectedClose)),
+            Some(c) => Err(self.error(ParseErrorKind::Unexpected(c as char))),
+            None => Err(self.error(ParseErrorKind::EndOfInput)),
+        }
+    }
+
+    fn number(&mut self) -> Result<Packet, ParseError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.position])
+            .unwrap()
+            .parse()
+            .map(Packet::Number)
+            .map_err(|_| ParseError {
+                position: start,
+                kind: ParseErrorKind::BadNumber,
             })
-            .and_then(|(remaining, parsed)| {
-                if !remaining.is_empty() {
-                    Err(anyhow::anyhow!(
-                        "There's some remaining text: {}",
-                        remaining
-                    ))
-                } else {
-                    Ok(parsed)
+    }
+
+    fn list(&mut self) -> Result<Packet, ParseError> {
+        let opened_at = self.position;
+        self.position += 1;
+        let mut packets = vec![];
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Packet::List(Box::new(packets)));
+        }
+        loop {
+            packets.push(self.packet()?);
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Packet::List(Box::new(packets)));
                 }
-            })
+                Some(c) => return Err(self.error(ParseErrorKind::Unexpected(c as char))),
+                None => return Err(self.error(ParseErrorKind::Unclosed { opened_at })),
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            position: 0,
+        };
+        let packet = parser.packet()?;
+        match parser.peek() {
+            None => Ok(packet),
+            Some(b']') => Err(parser.error(ParseErrorKind::UnexpectedClose)),
+            Some(_) => Err(parser.error(ParseErrorKind::TrailingInput)),
+        }
     }
 }
 
+// Where `packet` would land if `packets` were sorted, found by counting the
+// smaller ones rather than sorting.
+fn rank(packets: &[Packet], packet: &Packet) -> usize {
+    packets.iter().filter(|&p| p < packet).count()
+}
+
+// The same, for packets that are already in order.
+fn rank_sorted(sorted: &[Packet], packet: &Packet) -> usize {
+    sorted.partition_point(|p| p < packet)
+}
+
+// Adds `packet` to a list that's in order, keeping it that way, and returns
+// where it went.
+fn insert_sorted(sorted: &mut Vec<Packet>, packet: Packet) -> usize {
+    let position = rank_sorted(sorted, &packet);
+    sorted.insert(position, packet);
+    position
+}
+
 fn read_packets(filename: &str) -> Result<Vec<Packet>> {
     let mut packets = Vec::new();
     for line in std::fs::read_to_string(filename)?.lines() {
@@ -127,15 +220,17 @@ fn part1(packets: &[Packet]) -> u32 {
         .sum()
 }
 
+// The dividers are kept in order, so each one also has the dividers before
+// it ahead of it in the list.
 fn part2(packets: &[Packet]) -> u32 {
-    let dividers: Vec<Packet> = vec!["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
-    packets
+    let mut dividers = Vec::new();
+    for divider in ["[[2]]", "[[6]]"] {
+        insert_sorted(&mut dividers, divider.parse().unwrap());
+    }
+    dividers
         .iter()
-        .chain(dividers.iter())
-        .sorted()
         .enumerate()
-        .filter(|(_, p)| dividers.contains(p))
-        .map(|(i, _)| 1 + i as u32)
+        .map(|(i, divider)| (rank(packets, divider) + i + 1) as u32)
         .product()
 }
 
@@ -148,14 +243,75 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-#[test]
-fn test() -> Result<()> {
-    let packets = read_packets("input/test/day13.txt")?;
-    let result = part1(&packets);
-    assert_eq!(result, 13);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let result = part2(&packets);
-    assert_eq!(result, 140);
+    fn packet(s: &str) -> Packet {
+        s.parse().unwrap()
+    }
 
-    Ok(())
+    fn error(s: &str) -> String {
+        s.parse::<Packet>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test() -> Result<()> {
+        let packets = read_packets("input/test/day13.txt")?;
+        let result = part1(&packets);
+        assert_eq!(result, 13);
+
+        let result = part2(&packets);
+        assert_eq!(result, 140);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert_eq!(packet("1"), packet("[[1]]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[]") < packet("[[]]"));
+        assert!(packet("[[[]]]") > packet("[[]]"));
+        assert_eq!(packet("[1,[2]]").cmp(&packet("[[1],2]")), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_display() -> Result<()> {
+        let lines = std::fs::read_to_string("input/test/day13.txt")?;
+        for line in lines.lines().filter(|l| !l.is_empty()) {
+            assert_eq!(packet(line).to_string(), line);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(error("[1,[2,3]"), "'[' at 0 isn't closed by 8");
+        assert_eq!(error("[1,2]]"), "unmatched ']' at 5");
+        assert_eq!(error("[1,]"), "unmatched ']' at 3");
+        assert_eq!(error("[1 2]"), "unexpected ' ' at 2");
+        assert_eq!(error("[[1][2]]"), "unexpected '[' at 4");
+        assert_eq!(error("[a]"), "unexpected 'a' at 1");
+        assert_eq!(error("[99999]"), "number too big at 1");
+        assert_eq!(error("[1,"), "packet ends early at 3");
+        assert_eq!(error("[1]x"), "unexpected text after the packet at 3");
+    }
+
+    #[test]
+    fn test_ranks() -> Result<()> {
+        let mut packets = read_packets("input/test/day13.txt")?;
+        let divider = packet("[[6]]");
+        let rank_unsorted = rank(&packets, &divider);
+        packets.sort();
+        assert!(packets.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(rank_sorted(&packets, &divider), rank_unsorted);
+        assert_eq!(rank_unsorted, 12);
+        assert_eq!(insert_sorted(&mut packets, divider.clone()), 12);
+        assert_eq!(packets[12], divider);
+        assert_eq!(insert_sorted(&mut packets, packet("[[2]]")), 9);
+        assert!(packets.windows(2).all(|w| w[0] <= w[1]));
+        Ok(())
+    }
 }
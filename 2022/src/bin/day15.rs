@@ -1,7 +1,5 @@
-use gcollections::ops::*;
-
 use anyhow::Result;
-use interval::interval_set::*;
+use aoc_lib::intervals::{Interval, IntervalSet};
 use parse_display::FromStr;
 use utils::read_lines;
 
@@ -17,16 +15,16 @@ struct Sensor {
 }
 
 impl Sensor {
-    fn knowledge_range_at_row(&self, row: i64) -> IntervalSet<i64> {
+    fn knowledge_range_at_row(&self, row: i64) -> Option<Interval> {
         let furthest_distance = manhattan_distance(&self.location, &self.beacon);
         let distance_to_row = (self.location.1 - row).abs();
         let distance_to_edge = furthest_distance - distance_to_row;
         if distance_to_edge < 0 {
-            return IntervalSet::empty();
+            return None;
         }
         let left_edge = self.location.0 - distance_to_edge;
         let right_edge = self.location.0 + distance_to_edge;
-        vec![(left_edge, right_edge)].to_interval_set()
+        Some(Interval::new(left_edge, right_edge))
     }
 }
 
@@ -34,48 +32,30 @@ fn manhattan_distance(a: &Point, b: &Point) -> i64 {
     (a.0 - b.0).abs() + (a.1 - b.1).abs()
 }
 
-fn part1(sensors: &[Sensor], row: i64) -> u32 {
-    let beacons = sensors
-        .iter()
-        .filter(|s| s.beacon.1 == row)
-        .map(|s| IntervalSet::singleton(s.beacon.0))
-        .reduce(|a, b| a.union(&b))
-        .unwrap_or(IntervalSet::empty());
+fn knowledge_at_row(sensors: &[Sensor], row: i64) -> IntervalSet {
     sensors
         .iter()
-        .map(|s| s.knowledge_range_at_row(row))
-        .reduce(|a, b| a.union(&b))
-        .unwrap_or(IntervalSet::empty())
-        .difference(&beacons)
+        .filter_map(|s| s.knowledge_range_at_row(row))
+        .collect()
+}
+
+fn part1(sensors: &[Sensor], row: i64) -> u32 {
+    let beacons: IntervalSet = sensors
         .iter()
-        .map(|r| r.upper() - r.lower() + 1)
-        .sum::<i64>() as u32
+        .filter(|s| s.beacon.1 == row)
+        .map(|s| Interval::singleton(s.beacon.0))
+        .collect();
+    knowledge_at_row(sensors, row).difference(&beacons).len() as u32
 }
 
 fn part2(sensors: &[Sensor], bounds: (i64, i64)) -> Option<i64> {
-    let Some((y, x_range)) = (bounds.0..=bounds.1)
-        .map(|row| {
-            (
-                row,
-                sensors
-                    .iter()
-                    .map(|s| s.knowledge_range_at_row(row))
-                    .reduce(|a, b| a.union(&b))
-                    .unwrap_or(IntervalSet::empty()),
-            )
-        })
-        .filter(|(_, s)| s.iter().count() > 1 || s.lower() > bounds.0 || s.upper() < bounds.1)
-        .next() else {
-            return None;
-        };
-    let x = if x_range.lower() >= bounds.0 {
-        bounds.0
-    } else if x_range.upper() <= bounds.1 {
-        bounds.1
-    } else {
-        x_range.iter().fold(i64::MAX, |a, b| a.min(b.upper())) + 1
-    };
-    Some(x * 4000000 + y)
+    let search_area = IntervalSet::from(Interval::new(bounds.0, bounds.1));
+    (bounds.0..=bounds.1).find_map(|y| {
+        let x = search_area
+            .difference(&knowledge_at_row(sensors, y))
+            .min()?;
+        Some(x * 4000000 + y)
+    })
 }
 
 fn main() -> Result<()> {
@@ -2,6 +2,7 @@
 use std::str::FromStr;
 
 use anyhow::Result;
+use aoc_lib::intervals::{map_through, Interval, IntervalSet, RangeMap, RangeRule};
 use parse_display::FromStr;
 use utils::{read_file, InputParseError};
 
@@ -20,6 +21,10 @@ impl IdMapping {
         }
         return Some(self.destination_start - self.source_start + source);
     }
+
+    fn to_rule(&self) -> Option<RangeRule> {
+        RangeRule::new(self.destination_start, self.source_start, self.length)
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -73,6 +78,13 @@ impl Mappings {
             | Mappings::HumidityToLocation(mappings) => mappings,
         }
     }
+
+    fn range_map(&self) -> RangeMap {
+        self.get_id_mapping()
+            .iter()
+            .filter_map(IdMapping::to_rule)
+            .collect()
+    }
 }
 
 impl FromStr for Mappings {
@@ -124,15 +136,16 @@ fn part1(config: &Configuration) -> i64 {
         .unwrap()
 }
 
+// mappings must be sorted by the enum order
 fn part2(config: &Configuration) -> i64 {
-    config
+    let seeds: IntervalSet = config
         .seeds
         .iter()
         .array_chunks()
-        .flat_map(|[&start, &len]| (start..=start + len))
-        .map(|seed| seed_to_location(&config.mappings, seed))
-        .min()
-        .unwrap()
+        .filter_map(|[&start, &len]| Interval::from_len(start, len))
+        .collect();
+    let maps: Vec<RangeMap> = config.mappings.iter().map(Mappings::range_map).collect();
+    map_through(&maps, &seeds).min().unwrap()
 }
 
 fn main() -> Result<()> {
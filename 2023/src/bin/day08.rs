@@ -4,6 +4,7 @@ use std::{
 };
 
 use anyhow::Result;
+use aoc_lib::number_theory::lcm_all;
 use itertools::{FoldWhile, Itertools};
 use parse_display::FromStr;
 use utils::{read_file, InputParseError};
@@ -150,25 +151,6 @@ fn part1(map: &Map) -> u64 {
     }
 }
 
-fn gcd(mut a: u64, mut b: u64) -> u64 {
-    while b != 0 {
-        let t = b;
-        b = a % b;
-        a = t;
-    }
-    a
-}
-
-fn lcm(nums: Vec<u64>) -> u64 {
-    let mut nums = nums;
-    nums.sort();
-    let mut result = nums[0];
-    for &num in &nums[1..] {
-        result = result * num / gcd(result, num);
-    }
-    result
-}
-
 fn part2(map: &Map) -> u64 {
     let location_to_options = to_hash_map(map);
     let a_keys: Vec<_> = location_to_options
@@ -206,7 +188,7 @@ fn part2(map: &Map) -> u64 {
             }
         })
         .collect();
-    lcm(loop_lengths)
+    lcm_all(loop_lengths.into_iter().map(|n| n as i64)) as u64
 }
 
 fn main() -> Result<()> {
@@ -1,4 +1,4 @@
-use std::{iter, marker::PhantomData, str::FromStr, vec};
+use std::{collections::HashMap, iter, marker::PhantomData, str::FromStr};
 
 use anyhow::Result;
 use parse_display::FromStr;
@@ -122,6 +122,10 @@ fn generate_state(groups: &[u64], gaps: &[u64], length: u64) -> Vec<Status> {
     state
 }
 
+// Lists every arrangement that fits the record. Far too slow for unfolded
+// records, but handy for seeing what count_arrangements is counting on small
+// ones; the tests use it to cross-check.
+#[allow(dead_code)]
 fn generate_possible_states<T: Clone>(record: &SpringRecord<T>) -> Vec<Vec<Status>> {
     // println!("generating possible states for {:?}", record);
     let gap_count = record.groups.len() - 1;
@@ -150,20 +154,61 @@ fn generate_possible_states<T: Clone>(record: &SpringRecord<T>) -> Vec<Vec<Statu
         .collect()
 }
 
-fn part1(records: &[SpringRecord<Folded>]) -> usize {
-    records
-        .iter()
-        .map(generate_possible_states)
-        .map(|s| s.len())
-        .sum()
+// Counts arrangements a spring at a time. The state after each spring is just
+// which group we're in and how far through it we are, so lots of different
+// prefixes end up in the same place and only need counting once.
+struct Arrangements<'a> {
+    state: &'a [Status],
+    groups: &'a [u64],
+    cache: HashMap<(usize, usize, u64), u64>,
 }
 
-fn part2(records: &[SpringRecord<Unfolded>]) -> usize {
-    records
-        .iter()
-        .map(generate_possible_states)
-        .map(|s| s.len())
-        .sum()
+impl<'a> Arrangements<'a> {
+    // `run` is the number of damaged springs so far in groups[group].
+    fn count(&mut self, position: usize, group: usize, run: u64) -> u64 {
+        if position == self.state.len() {
+            let finished = group == self.groups.len() && run == 0
+                || group + 1 == self.groups.len() && run == self.groups[group];
+            return finished as u64;
+        }
+        if let Some(&count) = self.cache.get(&(position, group, run)) {
+            return count;
+        }
+
+        let mut count = 0;
+        let spring = self.state[position];
+        if spring.matches(Status::Damaged) && group < self.groups.len() && run < self.groups[group]
+        {
+            count += self.count(position + 1, group, run + 1);
+        }
+        if spring.matches(Status::Operational) {
+            if run == 0 {
+                count += self.count(position + 1, group, 0);
+            } else if run == self.groups[group] {
+                count += self.count(position + 1, group + 1, 0);
+            }
+        }
+
+        self.cache.insert((position, group, run), count);
+        count
+    }
+}
+
+fn count_arrangements<T: Clone>(record: &SpringRecord<T>) -> u64 {
+    Arrangements {
+        state: &record.state,
+        groups: &record.groups,
+        cache: HashMap::new(),
+    }
+    .count(0, 0, 0)
+}
+
+fn part1(records: &[SpringRecord<Folded>]) -> u64 {
+    records.iter().map(count_arrangements).sum()
+}
+
+fn part2(records: &[SpringRecord<Unfolded>]) -> u64 {
+    records.iter().map(count_arrangements).sum()
 }
 
 fn main() -> Result<()> {
@@ -179,30 +224,28 @@ fn main() -> Result<()> {
 
 #[test]
 fn test() -> Result<()> {
-    // let record: SpringRecord = "???.### 1,1,3".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 1);
+    let cases = [
+        ("???.### 1,1,3", 1, 1),
+        (".??..??...?##. 1,1,3", 4, 16384),
+        ("?#?#?#?#?#?#?#? 1,3,1,6", 1, 1),
+        ("????.#...#... 4,1,1", 1, 16),
+        ("????.######..#####. 1,6,5", 4, 2500),
+        ("?###???????? 3,2,1", 10, 506250),
+    ];
+    for (line, folded, unfolded) in cases {
+        let record: SpringRecord<Folded> = line.parse()?;
+        assert_eq!(count_arrangements(&record), folded);
+        assert_eq!(generate_possible_states(&record).len() as u64, folded);
 
-    // let record: SpringRecord = ".??..??...?##. 1,1,3".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 4);
-
-    // let record: SpringRecord = "?#?#?#?#?#?#?#? 1,3,1,6".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 1);
-
-    // let record: SpringRecord = "????.#...#... 4,1,1".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 1);
-
-    // let record: SpringRecord = "????.######..#####. 1,6,5".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 4);
+        let record: SpringRecord<Unfolded> = line.parse()?;
+        assert_eq!(count_arrangements(&record), unfolded);
+    }
 
-    // let record: SpringRecord = "?###???????? 3,2,1".parse()?;
-    // let result = generate_possible_states(&record);
-    // assert_eq!(result.len(), 10);
     let records: Vec<SpringRecord<Folded>> = read_lines("input/test/day12.txt")?;
+    for record in &records {
+        let brute_force = generate_possible_states(record).len() as u64;
+        assert_eq!(count_arrangements(record), brute_force);
+    }
     let result = part1(&records);
     assert_eq!(result, 21);
 
@@ -1,10 +1,10 @@
 use std::{
-    collections::HashMap,
     hash::{Hash, Hasher},
     iter,
 };
 
 use anyhow::Result;
+use aoc_lib::cycles::detect_recurrence;
 use itertools::Itertools;
 use parse_display::{Display, FromStr};
 use utils::{read_grid, Grid};
@@ -149,44 +149,6 @@ fn spin_cycle(platform: &mut Platform) {
     }
 }
 
-#[derive(Debug)]
-struct Recurrence {
-    loop_start: usize,
-    load_history: Vec<u32>,
-}
-
-impl Recurrence {
-    fn load_at(&self, cycle: usize) -> u32 {
-        let index = if cycle < self.load_history.len() {
-            cycle
-        } else {
-            let loop_length = self.load_history.len() - self.loop_start;
-            let cycle_after_first_loop = cycle - self.load_history.len() - 1;
-            cycle_after_first_loop % loop_length + self.loop_start + 1
-        };
-        self.load_history[index]
-    }
-}
-
-fn detect_recurrence(platform: &mut Platform) -> Recurrence {
-    let mut platform_to_idx = HashMap::new();
-    let mut load_history = Vec::new();
-    let loop_start = loop {
-        let hash = hash_platform(&platform);
-        if platform_to_idx.contains_key(&hash) {
-            break platform_to_idx[&hash];
-        }
-        platform_to_idx.insert(hash, load_history.len());
-        load_history.push(platform.calculate_load());
-        spin_cycle(platform);
-    };
-
-    Recurrence {
-        loop_start,
-        load_history,
-    }
-}
-
 fn max_x_y(grid: &Grid<u32, Tile>) -> (u32, u32) {
     let max_x = grid.keys().map(|(x, _)| x).max().unwrap();
     let max_y = grid.keys().map(|(_, y)| y).max().unwrap();
@@ -200,7 +162,13 @@ fn part1(platform: &mut Platform) -> u32 {
 }
 
 fn part2(platform: &mut Platform) -> u32 {
-    detect_recurrence(platform).load_at(1_000_000_000)
+    detect_recurrence(
+        platform,
+        spin_cycle,
+        hash_platform,
+        Platform::calculate_load,
+    )
+    .at(1_000_000_000)
 }
 
 fn read_platform(path: &str) -> Result<Platform> {
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use pathfinding::prelude::dijkstra;
+use aoc_lib::search::astar;
 use utils::{read_grid, Grid};
 
 type Point = (i16, i16);
@@ -117,28 +117,36 @@ fn max_x_y(grid: &Grid<i16, i16>) -> (i16, i16) {
     (*max_x, *max_y)
 }
 
+fn manhattan_distance(a: &Point, b: &Point) -> i16 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
 fn part1(map: &Grid<i16, i16>) -> i16 {
-    let (max_x, max_y) = max_x_y(map);
+    let target = max_x_y(map);
     let start: (Point, Option<StraightPath>) = ((0, 0), None);
-    dijkstra(
-        &start,
-        |&(p, dir)| neighbor_nodes(map, &p, &dir),
-        |&(p, _)| p == (max_x, max_y),
+    astar(
+        &|&(p, dir): &(Point, Option<StraightPath>)| neighbor_nodes(map, &p, &dir),
+        [start],
+        |(p, _)| manhattan_distance(p, &target),
+        |&(p, _)| p == target,
     )
     .unwrap()
-    .1
+    .cost
 }
 
 fn part2(map: &Grid<i16, i16>) -> i16 {
     let target = max_x_y(map);
     let start: (Point, Option<StraightPath>) = ((0, 0), None);
-    dijkstra(
-        &start,
-        |&(p, dir)| neighbor_nodes_ultra_crucible(map, &target, &p, &dir),
+    astar(
+        &|&(p, dir): &(Point, Option<StraightPath>)| {
+            neighbor_nodes_ultra_crucible(map, &target, &p, &dir)
+        },
+        [start],
+        |(p, _)| manhattan_distance(p, &target),
         |&(p, _)| p == target,
     )
     .unwrap()
-    .1
+    .cost
 }
 
 fn main() -> Result<()> {
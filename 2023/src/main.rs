@@ -1,3 +1,122 @@
-fn main() {
-    println!("Hello, world!");
+mod tools;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use tools::client::{read_session, AocClient, DEFAULT_BASE_URL, MIN_REQUEST_INTERVAL};
+use tools::extract::extract_examples;
+use tools::fetch::fetch_input;
+use tools::new_day::{new_day, InputShape};
+use tools::runner::{registered_years, run_all, run_day};
+use tools::submit::{history_path, submit, History};
+use tools::{repo_root, year_dir};
+
+#[derive(Parser)]
+#[command(about = "Advent of Code helpers")]
+struct Cli {
+    #[arg(long, global = true, default_value_t = 2023)]
+    year: u16,
+    #[arg(long, global = true, env = "AOC_BASE_URL", default_value = DEFAULT_BASE_URL)]
+    base_url: String,
+    #[arg(long, global = true)]
+    session: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Generate src/bin/dayNN.rs plus empty real and test inputs")]
+    NewDay {
+        day: u8,
+        #[arg(long, value_enum, default_value_t = InputShape::Lines)]
+        shape: InputShape,
+    },
+    #[command(about = "Build and run a single registered day")]
+    Run { day: u8 },
+    #[command(about = "Run every registered day of the year (or all years) in parallel")]
+    RunAll {
+        #[arg(long)]
+        all_years: bool,
+    },
+    #[command(about = "Download puzzle inputs into input/dayNN.txt unless already cached")]
+    Fetch {
+        #[arg(required = true)]
+        days: Vec<u8>,
+    },
+    #[command(about = "Submit an answer, refusing ones already known to be wrong")]
+    Submit {
+        day: u8,
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+        answer: String,
+    },
+    #[command(about = "Turn the examples in a saved puzzle page into test inputs")]
+    Extract {
+        day: u8,
+        description: PathBuf,
+        #[arg(long, value_enum, default_value_t = InputShape::Lines)]
+        shape: InputShape,
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl Cli {
+    fn client(&self) -> Result<AocClient> {
+        let session = read_session(self.session.clone(), &repo_root())?;
+        Ok(AocClient::new(
+            &self.base_url,
+            &session,
+            MIN_REQUEST_INTERVAL,
+        ))
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let year_dir = year_dir(cli.year);
+    match &cli.command {
+        Command::NewDay { day, shape } => {
+            let path = new_day(&year_dir, cli.year, *day, *shape)?;
+            println!("created {}", path.display());
+        }
+        Command::Run { day } => run_day(&year_dir, *day)?,
+        Command::RunAll { all_years } => {
+            let years = if *all_years {
+                registered_years(&repo_root())?
+            } else {
+                vec![cli.year]
+            };
+            run_all(&repo_root(), &years)?;
+        }
+        Command::Fetch { days } => {
+            let mut client = cli.client()?;
+            for &day in days {
+                let path = fetch_input(&mut client, &year_dir, cli.year, day)?;
+                println!("{}", path.display());
+            }
+        }
+        Command::Submit { day, part, answer } => {
+            let mut client = cli.client()?;
+            let mut history = History::load(&history_path(&year_dir))?;
+            let verdict = submit(&mut client, &mut history, cli.year, *day, *part, answer)?;
+            println!("{}", verdict);
+        }
+        Command::Extract {
+            day,
+            description,
+            shape,
+            force,
+        } => {
+            shape.check_year(cli.year)?;
+            let (fixtures, test) = extract_examples(&year_dir, description, *day, *shape, *force)?;
+            for path in fixtures {
+                eprintln!("wrote {}", path.display());
+            }
+            print!("{}", test);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,86 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(5);
+const USER_AGENT: &str = "github.com/DrGodCarl/AdventOfCode";
+const SESSION_FILE: &str = ".aoc-session";
+
+// The session token comes from `--session`/`AOC_SESSION` first, falling back
+// to a `.aoc-session` file at the root of the repository.
+pub fn read_session(explicit: Option<String>, repo_root: &Path) -> Result<String> {
+    if let Some(session) = explicit.or_else(|| env::var("AOC_SESSION").ok()) {
+        return Ok(session.trim().to_string());
+    }
+    let path = repo_root.join(SESSION_FILE);
+    let session = fs::read_to_string(&path)
+        .with_context(|| format!("no session token in AOC_SESSION or {}", path.display()))?;
+    Ok(session.trim().to_string())
+}
+
+pub struct AocClient {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    min_interval: Duration,
+    last_request: Option<Instant>,
+}
+
+impl AocClient {
+    pub fn new(base_url: &str, session: &str, min_interval: Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            min_interval,
+            last_request: None,
+        }
+    }
+
+    fn throttle(&mut self) {
+        if let Some(last) = self.last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    pub fn get(&mut self, path: &str) -> Result<String> {
+        self.throttle();
+        let url = self.url(path);
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &self.cookie())
+            .call()
+            .map_err(|e| anyhow!("GET {} failed: {}", url, e))?;
+        Ok(response.into_string()?)
+    }
+
+    pub fn post_form(&mut self, path: &str, fields: &[(&str, &str)]) -> Result<String> {
+        self.throttle();
+        let url = self.url(path);
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &self.cookie())
+            .send_form(fields)
+            .map_err(|e| anyhow!("POST {} failed: {}", url, e))?;
+        Ok(response.into_string()?)
+    }
+}
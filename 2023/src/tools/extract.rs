@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+
+use super::day_name;
+use super::new_day::InputShape;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PartExamples {
+    pub examples: Vec<String>,
+    pub answer: Option<String>,
+}
+
+fn unescape(html: &str) -> String {
+    html.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape(&text)
+}
+
+fn between<'a>(html: &'a str, open: &'a str, close: &'a str) -> impl Iterator<Item = &'a str> {
+    html.split(open)
+        .skip(1)
+        .filter_map(move |chunk| chunk.split_once(close).map(|(inside, _)| inside))
+}
+
+// Each `<article>` is one part of the puzzle. Examples are the `<pre><code>`
+// blocks and the expected answer is the last `<code><em>` in the article.
+pub fn parse_description(html: &str) -> Vec<PartExamples> {
+    html.split("<article")
+        .skip(1)
+        .map(|article| {
+            let article = article.split("</article>").next().unwrap_or(article);
+            let examples = between(article, "<pre><code>", "</code></pre>")
+                .map(|block| strip_tags(block).trim_end_matches('\n').to_string())
+                .collect();
+            let answer = between(article, "<code><em>", "</em></code>")
+                .last()
+                .map(strip_tags);
+            PartExamples { examples, answer }
+        })
+        .collect()
+}
+
+fn fixture_name(day: u8, index: usize) -> String {
+    match index {
+        0 => format!("{}.txt", day_name(day)),
+        _ => format!("{}_{}.txt", day_name(day), index + 1),
+    }
+}
+
+fn write_fixture(path: &Path, contents: &str, force: bool) -> Result<()> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    if !existing.is_empty() && existing != contents && !force {
+        bail!("{} already has different contents", path.display());
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn test_skeleton(day: u8, shape: InputShape, parts: &[(usize, Option<String>)]) -> String {
+    let mut test = String::from("#[test]\nfn test() -> Result<()> {\n");
+    for (part, (fixture, answer)) in parts.iter().enumerate() {
+        let path = format!("input/test/{}", fixture_name(day, *fixture));
+        let (load, args) = shape.test_load(&path);
+        test.push_str(&format!("    {}\n", load));
+        test.push_str(&format!("    let result = part{}({});\n", part + 1, args));
+        test.push_str(&format!(
+            "    assert_eq!(result, {});\n",
+            answer.as_deref().unwrap_or("todo!()")
+        ));
+    }
+    test.push_str("    Ok(())\n}\n");
+    test
+}
+
+// Writes every distinct example as input/test/dayNN.txt, dayNN_2.txt, ... and
+// returns a test function for the day that checks each part's answer.
+pub fn extract_examples(
+    year_dir: &Path,
+    description: &Path,
+    day: u8,
+    shape: InputShape,
+    force: bool,
+) -> Result<(Vec<PathBuf>, String)> {
+    let parts = parse_description(&fs::read_to_string(description)?);
+    if parts.is_empty() {
+        bail!("no puzzle articles found in {}", description.display());
+    }
+    let mut fixtures: Vec<String> = Vec::new();
+    let mut tested = Vec::new();
+    for part in parts.iter().take(2) {
+        for example in &part.examples {
+            if !fixtures.contains(example) {
+                fixtures.push(example.clone());
+            }
+        }
+        // A part without its own example is checked against the previous one.
+        let fixture = part
+            .examples
+            .first()
+            .and_then(|e| fixtures.iter().position(|f| f == e))
+            .unwrap_or(fixtures.len().saturating_sub(1));
+        tested.push((fixture, part.answer.clone()));
+    }
+    if fixtures.is_empty() {
+        bail!("no examples found in {}", description.display());
+    }
+
+    let test_dir = year_dir.join("input/test");
+    fs::create_dir_all(&test_dir)?;
+    let paths = fixtures
+        .iter()
+        .enumerate()
+        .map(|(i, contents)| {
+            let path = test_dir.join(fixture_name(day, i));
+            write_fixture(&path, contents, force)?;
+            Ok(path)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((paths, test_skeleton(day, shape, &tested)))
+}
+
+#[test]
+fn test() -> Result<()> {
+    let html = r#"<main>
+<article class="day-desc"><h2>--- Day 9: Mirage Maintenance ---</h2>
+<p>For example:</p>
+<pre><code>0 3 6 9 12 15
+1 3 6 10 15 21
+</code></pre>
+<p>Here, the answer is <code><em>114</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>1</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>For example, <code>a &lt;- b</code>:</p>
+<pre><code><em>5</em>  10  &amp;
+</code></pre>
+<p>Adding the new values gives <code><em>2</em></code>.</p>
+</article>
+</main>"#;
+    assert_eq!(
+        parse_description(html),
+        vec![
+            PartExamples {
+                examples: vec!["0 3 6 9 12 15\n1 3 6 10 15 21".to_string()],
+                answer: Some("114".to_string()),
+            },
+            PartExamples {
+                examples: vec!["5  10  &".to_string()],
+                answer: Some("2".to_string()),
+            },
+        ]
+    );
+
+    let year_dir = std::env::temp_dir().join(format!("aoc-extract-{}", std::process::id()));
+    fs::create_dir_all(&year_dir)?;
+    let description = year_dir.join("day09.html");
+    fs::write(&description, html)?;
+    let (paths, test) = extract_examples(&year_dir, &description, 9, InputShape::Lines, false)?;
+    assert_eq!(
+        paths,
+        vec![
+            year_dir.join("input/test/day09.txt"),
+            year_dir.join("input/test/day09_2.txt")
+        ]
+    );
+    assert_eq!(fs::read_to_string(&paths[1])?, "5  10  &");
+    assert!(test.contains("read_lines(\"input/test/day09_2.txt\")?;\n    let result = part2(&values);\n    assert_eq!(result, 2);"));
+
+    fs::write(&paths[0], "changed")?;
+    assert!(extract_examples(&year_dir, &description, 9, InputShape::Lines, false).is_err());
+    assert!(extract_examples(&year_dir, &description, 9, InputShape::Lines, true).is_ok());
+
+    fs::remove_dir_all(&year_dir)?;
+    Ok(())
+}
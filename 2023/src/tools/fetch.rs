@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::client::AocClient;
+use super::day_name;
+
+pub fn input_path(year_dir: &Path, day: u8) -> PathBuf {
+    year_dir
+        .join("input")
+        .join(format!("{}.txt", day_name(day)))
+}
+
+// new-day leaves an empty placeholder behind, so only a non-empty file counts
+// as a cached input.
+fn is_cached(path: &Path) -> bool {
+    fs::metadata(path).map(|m| m.len() > 0).unwrap_or(false)
+}
+
+pub fn fetch_input(client: &mut AocClient, year_dir: &Path, year: u16, day: u8) -> Result<PathBuf> {
+    let path = input_path(year_dir, day);
+    if is_cached(&path) {
+        return Ok(path);
+    }
+    let input = client.get(&format!("/{}/day/{}/input", year, day))?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, input)?;
+    Ok(path)
+}
+
+#[test]
+fn test() -> Result<()> {
+    use super::stub_server::StubServer;
+    use std::time::{Duration, Instant};
+
+    let server = StubServer::start(|request| {
+        if request.starts_with("GET /2023/day/3/input ") && request.contains("session=abc") {
+            (200, "467..114..\n...*......\n".to_string())
+        } else {
+            (404, "not found".to_string())
+        }
+    });
+    let year_dir = std::env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+    let mut client = AocClient::new(&server.base_url, "abc", Duration::from_millis(200));
+
+    let path = fetch_input(&mut client, &year_dir, 2023, 3)?;
+    assert_eq!(fs::read_to_string(&path)?, "467..114..\n...*......\n");
+    fetch_input(&mut client, &year_dir, 2023, 3)?;
+    assert_eq!(server.requests().len(), 1);
+
+    let start = Instant::now();
+    assert!(fetch_input(&mut client, &year_dir, 2023, 4).is_err());
+    assert!(fetch_input(&mut client, &year_dir, 2023, 5).is_err());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert!(!is_cached(&input_path(&year_dir, 4)));
+
+    fs::remove_dir_all(&year_dir)?;
+    Ok(())
+}
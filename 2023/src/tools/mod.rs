@@ -0,0 +1,26 @@
+use std::path::{Path, PathBuf};
+
+pub mod client;
+pub mod extract;
+pub mod fetch;
+pub mod new_day;
+pub mod runner;
+#[cfg(test)]
+mod stub_server;
+pub mod submit;
+
+pub fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+// Every year lives next to this crate, e.g. `../2022`.
+pub fn year_dir(year: u16) -> PathBuf {
+    repo_root().join(year.to_string())
+}
+
+pub fn day_name(day: u8) -> String {
+    format!("day{:02}", day)
+}
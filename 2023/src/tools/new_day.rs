@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+use super::day_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputShape {
+    Lines,
+    Chunks,
+    Grid,
+    Sections,
+    CommaSeparated,
+}
+
+impl InputShape {
+    fn template(&self) -> &'static str {
+        match self {
+            InputShape::Lines => include_str!("../../templates/lines.rs.tmpl"),
+            InputShape::Chunks => include_str!("../../templates/chunks.rs.tmpl"),
+            InputShape::Grid => include_str!("../../templates/grid.rs.tmpl"),
+            InputShape::Sections => include_str!("../../templates/sections.rs.tmpl"),
+            InputShape::CommaSeparated => include_str!("../../templates/comma.rs.tmpl"),
+        }
+    }
+
+    // Grid and Sections lean on Grid, read_sections and VecWrapper, which only
+    // the 2022 and 2023 utils have.
+    pub fn check_year(&self, year: u16) -> Result<()> {
+        let first_year = match self {
+            InputShape::Grid | InputShape::Sections => 2022,
+            InputShape::Lines | InputShape::Chunks | InputShape::CommaSeparated => 2020,
+        };
+        if year < first_year {
+            bail!(
+                "{:?} inputs need the utils from {} on, not {}",
+                self,
+                first_year,
+                year
+            );
+        }
+        Ok(())
+    }
+
+    // The statement loading a test input and the arguments passed to part1/part2,
+    // matching what the templates' own tests do.
+    pub fn test_load(&self, path: &str) -> (String, &'static str) {
+        match self {
+            InputShape::Lines => (
+                format!("let values: Vec<String> = read_lines(\"{}\")?;", path),
+                "&values",
+            ),
+            InputShape::Chunks => (
+                format!("let chunks: Vec<String> = read_chunks(\"{}\")?;", path),
+                "&chunks",
+            ),
+            InputShape::Grid => (format!("let grid = read_grid(\"{}\")?;", path), "&grid"),
+            InputShape::Sections => (
+                format!("let (first, second) = read_sections(\"{}\")?;", path),
+                "&first, &second",
+            ),
+            InputShape::CommaSeparated => (
+                format!(
+                    "let values: Vec<String> = read_comma_separated(\"{}\")?;",
+                    path
+                ),
+                "&values",
+            ),
+        }
+    }
+}
+
+fn render(shape: InputShape, day: u8) -> String {
+    shape.template().replace("{{day}}", &format!("{:02}", day))
+}
+
+fn create_if_missing(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if !path.exists() {
+        fs::write(path, "")?;
+    }
+    Ok(())
+}
+
+// Cargo picks up everything in src/bin, so writing the binary there is what
+// registers the day with the runner.
+pub fn new_day(year_dir: &Path, year: u16, day: u8, shape: InputShape) -> Result<PathBuf> {
+    if !(1..=25).contains(&day) {
+        bail!("day must be between 1 and 25, got {}", day);
+    }
+    shape.check_year(year)?;
+    let name = day_name(day);
+    let bin = year_dir.join("src/bin").join(format!("{}.rs", name));
+    if bin.exists() {
+        bail!("{} already exists", bin.display());
+    }
+    fs::create_dir_all(bin.parent().unwrap())?;
+    fs::write(&bin, render(shape, day))?;
+    create_if_missing(&year_dir.join("input").join(format!("{}.txt", name)))?;
+    create_if_missing(&year_dir.join("input/test").join(format!("{}.txt", name)))?;
+    Ok(bin)
+}
+
+#[test]
+fn test() -> Result<()> {
+    let year_dir = std::env::temp_dir().join(format!("aoc-new-day-{}", std::process::id()));
+    let bin = new_day(&year_dir, 2023, 7, InputShape::Grid)?;
+    let source = fs::read_to_string(&bin)?;
+    assert!(source.contains("read_grid(\"input/day07.txt\")"));
+    assert!(source.contains("read_grid(\"input/test/day07.txt\")"));
+    assert!(year_dir.join("input/day07.txt").exists());
+    assert!(year_dir.join("input/test/day07.txt").exists());
+    assert_eq!(super::runner::registered_days(&year_dir)?, vec![7]);
+    assert!(new_day(&year_dir, 2023, 7, InputShape::Lines).is_err());
+    assert!(new_day(&year_dir, 2023, 26, InputShape::Lines).is_err());
+    for shape in [InputShape::Grid, InputShape::Sections] {
+        assert!(new_day(&year_dir, 2021, 8, shape).is_err());
+    }
+    assert!(!year_dir.join("src/bin/day08.rs").exists());
+    assert!(new_day(&year_dir, 2020, 8, InputShape::Chunks).is_ok());
+    fs::remove_dir_all(&year_dir)?;
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+
+use super::day_name;
+
+pub fn registered_days(year_dir: &Path) -> Result<Vec<u8>> {
+    let mut days = fs::read_dir(year_dir.join("src/bin"))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("day")?
+                .strip_suffix(".rs")?
+                .parse()
+                .ok()
+        })
+        .collect::<Vec<u8>>();
+    days.sort();
+    Ok(days)
+}
+
+pub fn run_day(year_dir: &Path, day: u8) -> Result<()> {
+    if !registered_days(year_dir)?.contains(&day) {
+        bail!(
+            "{} is not registered in {}",
+            day_name(day),
+            year_dir.display()
+        );
+    }
+    let status = Command::new("cargo")
+        .args(["run", "--release", "--bin", &day_name(day)])
+        .current_dir(year_dir)
+        .status()?;
+    if !status.success() {
+        bail!("{} exited with {}", day_name(day), status);
+    }
+    Ok(())
+}
+
+pub fn registered_years(repo_root: &Path) -> Result<Vec<u16>> {
+    let mut years = fs::read_dir(repo_root)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("Cargo.toml").exists())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect::<Vec<u16>>();
+    years.sort();
+    Ok(years)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub year: u16,
+    pub day: u8,
+    pub year_dir: PathBuf,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Solved(String),
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct DayRun {
+    pub job: Job,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for DayRun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = format!("{} {}", self.job.year, day_name(self.job.day));
+        match &self.outcome {
+            Outcome::Solved(output) => write!(
+                f,
+                "{} {:>9.3}s  {}",
+                label,
+                self.elapsed.as_secs_f64(),
+                output.lines().collect::<Vec<_>>().join(", ")
+            ),
+            Outcome::Failed(reason) => write!(f, "{}    FAILED  {}", label, reason),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+// Results come back in the same order as `jobs` no matter which finishes first,
+// and a panic inside one job is reported as that job's failure.
+pub fn run_jobs<F>(jobs: &[Job], run: F) -> Vec<DayRun>
+where
+    F: Fn(&Job) -> Outcome + Sync,
+{
+    jobs.par_iter()
+        .map(|job| {
+            let start = Instant::now();
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| run(job)))
+                .unwrap_or_else(|payload| Outcome::Failed(panic_message(payload)));
+            DayRun {
+                job: job.clone(),
+                outcome,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+fn build_release(year_dir: &Path) -> Result<()> {
+    let output = Command::new("cargo")
+        .args(["build", "--release", "--bins", "--quiet"])
+        .current_dir(year_dir)
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", last_line(&output.stderr));
+    }
+    Ok(())
+}
+
+fn last_line(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+// Each day runs as its own already-built binary, so a solver that panics or
+// exits with an error only fails its own line of the report.
+fn run_binary(job: &Job) -> Outcome {
+    let binary = job.year_dir.join("target/release").join(day_name(job.day));
+    match Command::new(binary).current_dir(&job.year_dir).output() {
+        Ok(output) if output.status.success() => {
+            Outcome::Solved(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        Ok(output) => Outcome::Failed(last_line(&output.stderr)),
+        Err(e) => Outcome::Failed(e.to_string()),
+    }
+}
+
+pub fn run_all(repo_root: &Path, years: &[u16]) -> Result<()> {
+    let start = Instant::now();
+    let mut jobs = Vec::new();
+    for &year in years {
+        let year_dir = repo_root.join(year.to_string());
+        if let Err(e) = build_release(&year_dir) {
+            println!("{}           FAILED  build: {}", year, e);
+            continue;
+        }
+        jobs.extend(registered_days(&year_dir)?.into_iter().map(|day| Job {
+            year,
+            day,
+            year_dir: year_dir.clone(),
+        }));
+    }
+    for run in run_jobs(&jobs, run_binary) {
+        println!("{}", run);
+    }
+    println!("total: {:.3}s", start.elapsed().as_secs_f64());
+    Ok(())
+}
+
+#[test]
+fn test() -> Result<()> {
+    let jobs = (1..=6)
+        .map(|day| Job {
+            year: 2023,
+            day,
+            year_dir: PathBuf::from("2023"),
+        })
+        .collect::<Vec<_>>();
+    let runs = run_jobs(&jobs, |job| {
+        std::thread::sleep(Duration::from_millis(10 * (6 - job.day as u64)));
+        if job.day == 3 {
+            panic!("broken solver");
+        }
+        Outcome::Solved(format!("part 1: {}", job.day))
+    });
+    assert_eq!(
+        runs.iter().map(|r| r.job.day).collect::<Vec<_>>(),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        runs[2].outcome,
+        Outcome::Failed("broken solver".to_string())
+    );
+    assert_eq!(runs[5].outcome, Outcome::Solved("part 1: 6".to_string()));
+    assert!(runs[1].to_string().starts_with("2023 day02"));
+    Ok(())
+}
@@ -0,0 +1,303 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use parse_display::{Display, FromStr};
+
+use super::client::AocClient;
+use super::day_name;
+
+// Every wrong answer says "Please wait one minute before trying again". Repeat
+// offenders get longer, but the server reports that as a Wait when we try.
+const WRONG_ANSWER_WAIT: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromStr, Display)]
+pub enum Verdict {
+    #[display("correct")]
+    Correct,
+    #[display("too-high")]
+    TooHigh,
+    #[display("too-low")]
+    TooLow,
+    #[display("incorrect")]
+    Incorrect,
+    #[display("wait-{0}")]
+    Wait(u64),
+    #[display("already-solved")]
+    AlreadySolved,
+}
+
+// "You have 1m 23s left to wait." -> 83
+fn parse_wait(html: &str) -> Option<u64> {
+    let (before, _) = html.split_once("left to wait")?;
+    let (_, duration) = before.rsplit_once("You have")?;
+    duration
+        .split_whitespace()
+        .map(|part| {
+            if let Some(n) = part.strip_suffix('m') {
+                n.parse::<u64>().ok().map(|n| n * 60)
+            } else if let Some(n) = part.strip_suffix('s') {
+                n.parse::<u64>().ok()
+            } else {
+                None
+            }
+        })
+        .sum()
+}
+
+pub fn parse_verdict(html: &str) -> Result<Verdict> {
+    Ok(if html.contains("That's the right answer") {
+        Verdict::Correct
+    } else if html.contains("your answer is too high") {
+        Verdict::TooHigh
+    } else if html.contains("your answer is too low") {
+        Verdict::TooLow
+    } else if html.contains("That's not the right answer") {
+        Verdict::Incorrect
+    } else if let Some(seconds) = parse_wait(html) {
+        Verdict::Wait(seconds)
+    } else if html.contains("Did you already complete it") {
+        Verdict::AlreadySolved
+    } else {
+        bail!("unrecognised response from the puzzle server")
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, FromStr, Display)]
+#[display("{timestamp} {day} {part} {verdict} {answer}")]
+struct Attempt {
+    timestamp: u64,
+    day: u8,
+    part: u8,
+    verdict: Verdict,
+    answer: String,
+}
+
+pub fn history_path(year_dir: &Path) -> PathBuf {
+    year_dir.join("input").join("submissions.txt")
+}
+
+pub struct History {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl History {
+    pub fn load(path: &Path) -> Result<Self> {
+        let attempts = if path.exists() {
+            fs::read_to_string(path)?
+                .lines()
+                .map(|l| l.parse::<Attempt>())
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(History {
+            path: path.to_path_buf(),
+            attempts,
+        })
+    }
+
+    fn attempts_for(&self, day: u8, part: u8) -> impl Iterator<Item = &Attempt> {
+        self.attempts
+            .iter()
+            .filter(move |a| a.day == day && a.part == part)
+    }
+
+    // Exclusive (lower, upper) bounds learned from too-low/too-high responses.
+    pub fn bounds(&self, day: u8, part: u8) -> (Option<i64>, Option<i64>) {
+        self.attempts_for(day, part)
+            .fold((None, None), |(low, high), attempt| {
+                match (attempt.verdict, attempt.answer.parse::<i64>()) {
+                    (Verdict::TooLow, Ok(n)) => (low.max(Some(n)), high),
+                    (Verdict::TooHigh, Ok(n)) => (low, Some(high.map_or(n, |h: i64| h.min(n)))),
+                    _ => (low, high),
+                }
+            })
+    }
+
+    fn check(&self, day: u8, part: u8, answer: &str, now: u64) -> Result<()> {
+        let name = day_name(day);
+        if let Some(wait_until) = self
+            .attempts
+            .iter()
+            .filter(|a| a.day == day)
+            .filter_map(|a| match a.verdict {
+                Verdict::Wait(seconds) => Some(a.timestamp + seconds),
+                Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect => {
+                    Some(a.timestamp + WRONG_ANSWER_WAIT)
+                }
+                _ => None,
+            })
+            .max()
+        {
+            if wait_until > now {
+                bail!("{} is throttled for another {}s", name, wait_until - now);
+            }
+        }
+        if let Some(correct) = self
+            .attempts_for(day, part)
+            .find(|a| a.verdict == Verdict::Correct)
+        {
+            bail!(
+                "{} part {} was already solved with {}",
+                name,
+                part,
+                correct.answer
+            );
+        }
+        if let Some(wrong) = self
+            .attempts_for(day, part)
+            .filter(|a| a.answer == answer)
+            .find(|a| {
+                matches!(
+                    a.verdict,
+                    Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect
+                )
+            })
+        {
+            bail!("{} was already submitted and was {}", answer, wrong.verdict);
+        }
+        if let Ok(n) = answer.parse::<i64>() {
+            match self.bounds(day, part) {
+                (Some(low), _) if n <= low => bail!("{} is too low, it must be above {}", n, low),
+                (_, Some(high)) if n >= high => {
+                    bail!("{} is too high, it must be below {}", n, high)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, attempt: Attempt) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", attempt)?;
+        self.attempts.push(attempt);
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn submit(
+    client: &mut AocClient,
+    history: &mut History,
+    year: u16,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Verdict> {
+    submit_at(client, history, year, day, part, answer, unix_now())
+}
+
+fn submit_at(
+    client: &mut AocClient,
+    history: &mut History,
+    year: u16,
+    day: u8,
+    part: u8,
+    answer: &str,
+    now: u64,
+) -> Result<Verdict> {
+    let answer = answer.trim();
+    history.check(day, part, answer, now)?;
+    let html = client.post_form(
+        &format!("/{}/day/{}/answer", year, day),
+        &[("level", &part.to_string()), ("answer", answer)],
+    )?;
+    let verdict = parse_verdict(&html)?;
+    history.record(Attempt {
+        timestamp: now,
+        day,
+        part,
+        verdict,
+        answer: answer.to_string(),
+    })?;
+    Ok(verdict)
+}
+
+#[test]
+fn test() -> Result<()> {
+    use super::stub_server::StubServer;
+    use std::time::Duration;
+
+    fn page(message: &str) -> String {
+        format!(
+            "<html><body><main><article><p>{}</p></article></main></body></html>",
+            message
+        )
+    }
+
+    let server = StubServer::start(|request| {
+        let body = request.rsplit("\r\n").next().unwrap_or_default();
+        let message = match body {
+            "level=1&answer=100" => "That's not the right answer; your answer is too high. Please wait one minute before trying again.",
+            "level=1&answer=10" => "That's not the right answer; your answer is too low.",
+            "level=1&answer=50" => "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2023/day/9\">[Return to Day 9]</a>",
+            "level=2&answer=42" => "That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to collecting enough star fruit.",
+            _ => "That's not the right answer.",
+        };
+        (200, page(message))
+    });
+    let path = std::env::temp_dir().join(format!("aoc-submit-{}.txt", std::process::id()));
+    let mut client = AocClient::new(&server.base_url, "abc", Duration::ZERO);
+    let mut history = History::load(&path)?;
+    let mut submit =
+        |day, part, answer, now| submit_at(&mut client, &mut history, 2023, day, part, answer, now);
+
+    assert_eq!(submit(9, 1, "100", 1000)?, Verdict::TooHigh);
+    assert!(server.requests()[0].starts_with("POST /2023/day/9/answer "));
+    assert!(submit(9, 1, "100", 1000).is_err());
+    assert!(submit(9, 1, "10", 1059).is_err());
+    assert!(submit(9, 1, "150", 1060).is_err());
+    assert_eq!(submit(9, 1, "10", 1060)?, Verdict::TooLow);
+    assert!(submit(9, 1, "5", 1120).is_err());
+    assert_eq!(server.requests().len(), 2);
+
+    assert_eq!(submit(9, 1, "50", 1120)?, Verdict::Wait(65));
+    assert!(submit(9, 1, "50", 1180).is_err());
+    assert_eq!(server.requests().len(), 3);
+
+    // Past every wait, known wrong answers are still refused without asking.
+    let error = submit(9, 1, "100", 1200).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "100 was already submitted and was too-high"
+    );
+    assert_eq!(submit(9, 2, "abc", 1200)?, Verdict::Incorrect);
+    let error = submit(9, 2, "abc", 1300).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "abc was already submitted and was incorrect"
+    );
+    assert_eq!(server.requests().len(), 4);
+
+    assert_eq!(submit(10, 2, "42", 1180)?, Verdict::Correct);
+    assert!(submit(10, 2, "43", 1180).is_err());
+    assert_eq!(server.requests().len(), 5);
+    assert_eq!(history.bounds(9, 1), (Some(10), Some(100)));
+
+    let reloaded = History::load(&path)?;
+    assert_eq!(reloaded.attempts, history.attempts);
+    assert_eq!(reloaded.bounds(9, 1), (Some(10), Some(100)));
+
+    assert_eq!(parse_wait("You have 2m 5é left to wait."), None);
+    assert_eq!(parse_wait("You have 1mé left to wait."), None);
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
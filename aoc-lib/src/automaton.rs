@@ -0,0 +1,440 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// The cells whose states feed into a cell's next state, as offsets from it.
+// The order of the offsets is the order the rule sees the neighbours in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbourhood<const N: usize> {
+    offsets: Vec<[i64; N]>,
+}
+
+impl<const N: usize> Neighbourhood<N> {
+    pub fn new(offsets: Vec<[i64; N]>) -> Self {
+        Self { offsets }
+    }
+
+    // Every cell within one step along any combination of axes, in
+    // lexicographic order of the offsets.
+    pub fn moore() -> Self {
+        let offsets = (0..3usize.pow(N as u32))
+            .map(|mut i| {
+                let mut offset = [0; N];
+                for axis in (0..N).rev() {
+                    offset[axis] = (i % 3) as i64 - 1;
+                    i /= 3;
+                }
+                offset
+            })
+            .filter(|offset| offset.iter().any(|&d| d != 0))
+            .collect();
+        Self { offsets }
+    }
+
+    // One step along a single axis.
+    pub fn von_neumann() -> Self {
+        let offsets = (0..N)
+            .flat_map(|axis| {
+                [-1, 1].iter().map(move |&d| {
+                    let mut offset = [0; N];
+                    offset[axis] = d;
+                    offset
+                })
+            })
+            .collect();
+        Self { offsets }
+    }
+
+    // For rules that read the cell itself as part of the neighbourhood, like
+    // image enhancement reading a 3x3 block. Keeps the offsets sorted.
+    pub fn with_origin(mut self) -> Self {
+        self.offsets.push([0; N]);
+        self.offsets.sort_unstable();
+        self
+    }
+
+    pub fn offsets(&self) -> &[[i64; N]] {
+        &self.offsets
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    pub fn around(&self, point: [i64; N]) -> impl Iterator<Item = [i64; N]> + '_ {
+        self.offsets.iter().map(move |offset| add(point, *offset))
+    }
+}
+
+impl Neighbourhood<2> {
+    // The six neighbours of a hex in axial (q, r) coordinates: e, se, sw, w,
+    // nw, ne.
+    pub fn hex() -> Self {
+        Self::new(vec![[1, 0], [0, 1], [-1, 1], [-1, 0], [0, -1], [1, -1]])
+    }
+}
+
+fn add<const N: usize>(mut a: [i64; N], b: [i64; N]) -> [i64; N] {
+    for axis in 0..N {
+        a[axis] += b[axis];
+    }
+    a
+}
+
+fn sub<const N: usize>(mut a: [i64; N], b: [i64; N]) -> [i64; N] {
+    for axis in 0..N {
+        a[axis] -= b[axis];
+    }
+    a
+}
+
+// A rule gets the cell's current state and its neighbours' states, in
+// neighbourhood order, and returns the cell's next state.
+pub trait Automaton<S> {
+    // Applies the rule to every cell at once. Returns whether anything changed.
+    fn step<R: Fn(&S, &[S]) -> S>(&mut self, rule: R) -> bool;
+
+    fn run<R: Fn(&S, &[S]) -> S>(&mut self, steps: usize, rule: R) {
+        for _ in 0..steps {
+            self.step(&rule);
+        }
+    }
+
+    // Steps until a fixed point, returning how many steps changed something.
+    fn run_until_stable<R: Fn(&S, &[S]) -> S>(&mut self, rule: R) -> usize {
+        let mut steps = 0;
+        while self.step(&rule) {
+            steps += 1;
+        }
+        steps
+    }
+}
+
+// An unbounded grid in any number of dimensions. Only cells that differ from
+// the background are stored, and the background itself evolves with the rule,
+// so a universe that flips every empty cell at once is still finite.
+#[derive(Debug, Clone)]
+pub struct SparseAutomaton<S, const N: usize> {
+    cells: HashMap<[i64; N], S>,
+    background: S,
+    neighbourhood: Neighbourhood<N>,
+}
+
+impl<S: Clone + PartialEq, const N: usize> SparseAutomaton<S, N> {
+    pub fn new<I>(neighbourhood: Neighbourhood<N>, background: S, cells: I) -> Self
+    where
+        I: IntoIterator<Item = ([i64; N], S)>,
+    {
+        let mut automaton = Self {
+            cells: HashMap::new(),
+            background,
+            neighbourhood,
+        };
+        for (point, state) in cells {
+            automaton.set(point, state);
+        }
+        automaton
+    }
+
+    pub fn get(&self, point: &[i64; N]) -> &S {
+        self.cells.get(point).unwrap_or(&self.background)
+    }
+
+    pub fn set(&mut self, point: [i64; N], state: S) {
+        if state == self.background {
+            self.cells.remove(&point);
+        } else {
+            self.cells.insert(point, state);
+        }
+    }
+
+    pub fn background(&self) -> &S {
+        &self.background
+    }
+
+    // Every cell not in the background state.
+    pub fn cells(&self) -> impl Iterator<Item = (&[i64; N], &S)> {
+        self.cells.iter()
+    }
+
+    // Counts stored cells only; if `state` is the background there are
+    // infinitely many more.
+    pub fn count(&self, state: &S) -> usize {
+        self.cells.values().filter(|&s| s == state).count()
+    }
+
+    // The cells that can change: the stored ones and anything that has one of
+    // them in its neighbourhood.
+    fn candidates(&self) -> HashSet<[i64; N]> {
+        self.cells
+            .keys()
+            .flat_map(|&point| {
+                self.neighbourhood
+                    .offsets
+                    .iter()
+                    .map(move |&offset| sub(point, offset))
+                    .chain(std::iter::once(point))
+            })
+            .collect()
+    }
+}
+
+impl<S: Clone + PartialEq, const N: usize> Automaton<S> for SparseAutomaton<S, N> {
+    fn step<R: Fn(&S, &[S]) -> S>(&mut self, rule: R) -> bool {
+        let background = rule(
+            &self.background,
+            &vec![self.background.clone(); self.neighbourhood.len()],
+        );
+        let mut neighbours = Vec::with_capacity(self.neighbourhood.len());
+        let cells: HashMap<_, _> = self
+            .candidates()
+            .into_iter()
+            .filter_map(|point| {
+                neighbours.clear();
+                neighbours.extend(
+                    self.neighbourhood
+                        .around(point)
+                        .map(|p| self.get(&p).clone()),
+                );
+                let state = rule(self.get(&point), &neighbours);
+                (state != background).then_some((point, state))
+            })
+            .collect();
+        let changed = cells != self.cells || background != self.background;
+        self.cells = cells;
+        self.background = background;
+        changed
+    }
+}
+
+// A fixed-size 2D grid stored row by row. Each cell's neighbours are worked out
+// once up front, so neighbourhoods that depend on the starting layout, like
+// line of sight, cost nothing per step. Offsets are (row, column).
+#[derive(Debug, Clone)]
+pub struct DenseAutomaton<S> {
+    cells: Vec<S>,
+    width: usize,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl<S: Clone + PartialEq> DenseAutomaton<S> {
+    fn from_rows<F>(rows: Vec<Vec<S>>, find_neighbours: F) -> Self
+    where
+        F: Fn(&[S], usize, usize, usize) -> Vec<usize>,
+    {
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "every row must be the same width"
+        );
+        let cells: Vec<S> = rows.into_iter().flatten().collect();
+        let neighbours = (0..cells.len())
+            .map(|i| find_neighbours(&cells, width, i / width, i % width))
+            .collect();
+        Self {
+            cells,
+            width,
+            neighbours,
+        }
+    }
+
+    // Cells outside the grid are simply left out of the neighbourhood.
+    pub fn new(rows: Vec<Vec<S>>, neighbourhood: &Neighbourhood<2>) -> Self {
+        Self::from_rows(rows, |cells, width, row, column| {
+            let height = (cells.len() / width) as i64;
+            neighbourhood
+                .around([row as i64, column as i64])
+                .filter(|&[r, c]| (0..height).contains(&r) && (0..width as i64).contains(&c))
+                .map(|[r, c]| r as usize * width + c as usize)
+                .collect()
+        })
+    }
+
+    // Each cell's neighbours are the first `visible` cells found looking out
+    // along each direction, judged on the starting states.
+    pub fn line_of_sight<F>(rows: Vec<Vec<S>>, directions: &Neighbourhood<2>, visible: F) -> Self
+    where
+        F: Fn(&S) -> bool,
+    {
+        Self::from_rows(rows, |cells, width, row, column| {
+            let height = (cells.len() / width) as i64;
+            directions
+                .offsets()
+                .iter()
+                .filter_map(|&direction| {
+                    let mut point = [row as i64, column as i64];
+                    loop {
+                        point = add(point, direction);
+                        let [r, c] = point;
+                        if !(0..height).contains(&r) || !(0..width as i64).contains(&c) {
+                            return None;
+                        }
+                        let index = r as usize * width + c as usize;
+                        if visible(&cells[index]) {
+                            return Some(index);
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> &S {
+        &self.cells[row * self.width + column]
+    }
+
+    pub fn cells(&self) -> &[S] {
+        &self.cells
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[S]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn count(&self, state: &S) -> usize {
+        self.cells.iter().filter(|&s| s == state).count()
+    }
+}
+
+impl<S: Clone + PartialEq> Automaton<S> for DenseAutomaton<S> {
+    fn step<R: Fn(&S, &[S]) -> S>(&mut self, rule: R) -> bool {
+        let mut neighbours = Vec::new();
+        let cells: Vec<S> = self
+            .cells
+            .iter()
+            .zip(&self.neighbours)
+            .map(|(cell, indices)| {
+                neighbours.clear();
+                neighbours.extend(indices.iter().map(|&i| self.cells[i].clone()));
+                rule(cell, &neighbours)
+            })
+            .collect();
+        let changed = cells != self.cells;
+        self.cells = cells;
+        changed
+    }
+}
+
+impl<S: fmt::Display + Clone + PartialEq> fmt::Display for DenseAutomaton<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Counts the neighbours in a particular state, which is what most rules need.
+pub fn count<S: PartialEq>(neighbours: &[S], state: &S) -> usize {
+    neighbours.iter().filter(|&s| s == state).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(cell: &bool, neighbours: &[bool]) -> bool {
+        matches!((cell, count(neighbours, &true)), (true, 2..=3) | (false, 3))
+    }
+
+    fn glider() -> Vec<[i64; 2]> {
+        vec![[0, 1], [1, 2], [2, 0], [2, 1], [2, 2]]
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        assert_eq!(Neighbourhood::<2>::moore().len(), 8);
+        assert_eq!(Neighbourhood::<3>::moore().len(), 26);
+        assert_eq!(Neighbourhood::<4>::moore().len(), 80);
+        assert_eq!(Neighbourhood::<3>::von_neumann().len(), 6);
+        let block = Neighbourhood::<2>::moore().with_origin();
+        assert_eq!(block.offsets()[0], [-1, -1]);
+        assert_eq!(block.offsets()[4], [0, 0]);
+        assert_eq!(block.offsets()[8], [1, 1]);
+        assert_eq!(
+            Neighbourhood::hex()
+                .around([0, 0])
+                .collect::<HashSet<_>>()
+                .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_sparse_glider() {
+        let mut life_grid = SparseAutomaton::new(
+            Neighbourhood::moore(),
+            false,
+            glider().into_iter().map(|p| (p, true)),
+        );
+        life_grid.run(4, life);
+        let mut moved: Vec<_> = life_grid.cells().map(|(p, _)| *p).collect();
+        moved.sort_unstable();
+        let mut expected: Vec<_> = glider().into_iter().map(|p| add(p, [1, 1])).collect();
+        expected.sort_unstable();
+        assert_eq!(moved, expected);
+    }
+
+    #[test]
+    fn test_sparse_background_flips() {
+        // Every empty cell turns on and every full one turns off.
+        let mut grid = SparseAutomaton::new(
+            Neighbourhood::moore().with_origin(),
+            false,
+            vec![([0, 0], true)],
+        );
+        assert!(grid.step(|cell, _| !cell));
+        assert!(*grid.background());
+        assert_eq!(grid.count(&false), 1);
+        assert!(!*grid.get(&[0, 0]));
+        assert!(*grid.get(&[100, -100]));
+    }
+
+    #[test]
+    fn test_dense() {
+        let rows = vec![
+            vec![false, true, false],
+            vec![false, true, false],
+            vec![false, true, false],
+        ];
+        let mut blinker = DenseAutomaton::new(rows.clone(), &Neighbourhood::moore());
+        assert!(blinker.step(life));
+        assert_eq!(blinker.rows().nth(1), Some(&[true, true, true][..]));
+        blinker.step(life);
+        assert_eq!(
+            blinker.cells(),
+            DenseAutomaton::new(rows, &Neighbourhood::moore()).cells()
+        );
+
+        let mut block = DenseAutomaton::new(vec![vec![true; 2]; 2], &Neighbourhood::moore());
+        assert_eq!(block.run_until_stable(life), 0);
+        assert_eq!(block.count(&true), 4);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        // Zeros are see-through, so the corners can see each other.
+        let rows = vec![vec![1, 0, 2], vec![0, 0, 0], vec![3, 0, 4]];
+        let grid = DenseAutomaton::line_of_sight(rows, &Neighbourhood::moore(), |&c| c != 0);
+        let mut seen = grid.clone();
+        seen.step(|_, neighbours| neighbours.iter().sum());
+        assert_eq!(seen.cells(), &[9, 3, 8, 4, 10, 6, 7, 7, 6]);
+        assert_eq!(*grid.get(2, 2), 4);
+    }
+}
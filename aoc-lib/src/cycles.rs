@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// A sequence that, after `start` steps, repeats every `length` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    // The earliest step whose state is the same as the state after `step` steps.
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+}
+
+// Brent's algorithm: finds the cycle using only two copies of the state, for
+// when keeping every state around would be too expensive.
+pub fn brent<T, F>(initial: T, step: F) -> Cycle
+where
+    T: PartialEq + Clone,
+    F: Fn(&T) -> T,
+{
+    brent_within(initial, step, usize::MAX).expect("the sequence never repeats")
+}
+
+// The same, giving up once `limit` steps have gone by without the cycle
+// closing, for sequences that might take longer to repeat than is worth
+// waiting for.
+pub fn brent_within<T, F>(initial: T, step: F, limit: usize) -> Option<Cycle>
+where
+    T: PartialEq + Clone,
+    F: Fn(&T) -> T,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut steps = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if steps > limit {
+            return None;
+        }
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+        steps += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Some(Cycle { start, length })
+}
+
+// The cycle plus whatever was measured at every step before it closed.
+#[derive(Debug, Clone)]
+pub struct Recurrence<M> {
+    pub cycle: Cycle,
+    history: Vec<M>,
+}
+
+impl<M: Clone> Recurrence<M> {
+    pub fn at(&self, step: usize) -> M {
+        self.history[self.cycle.equivalent_step(step)].clone()
+    }
+}
+
+// Steps `state` in place until `key` repeats, recording `metric` after every
+// step (index 0 is the untouched initial state). `key` can be a cheap
+// projection such as a hash as long as it tells distinct states apart.
+pub fn detect_recurrence<S, K, M, F, P, G>(
+    state: &mut S,
+    mut step: F,
+    key: P,
+    metric: G,
+) -> Recurrence<M>
+where
+    K: Hash + Eq,
+    F: FnMut(&mut S),
+    P: Fn(&S) -> K,
+    G: Fn(&S) -> M,
+{
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    let start = loop {
+        if let Some(&start) = seen.get(&key(state)) {
+            break start;
+        }
+        seen.insert(key(state), history.len());
+        history.push(metric(state));
+        step(state);
+    };
+    Recurrence {
+        cycle: Cycle {
+            start,
+            length: history.len() - start,
+        },
+        history,
+    }
+}
+
+// The state after `steps` applications of `step`, however large `steps` is.
+pub fn state_at<T, F>(initial: T, step: F, steps: usize) -> T
+where
+    T: Clone + Hash + Eq,
+    F: Fn(&T) -> T,
+{
+    let mut state = initial;
+    detect_recurrence(&mut state, |s| *s = step(s), T::clone, T::clone).at(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(n: &u32) -> u32 {
+        if *n == 6 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(
+            brent(0, step),
+            Cycle {
+                start: 3,
+                length: 4
+            }
+        );
+        assert_eq!(
+            brent(5, step),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+        let f = |x: &u64| (x * x + 1) % 255;
+        let mut state = 3;
+        let recurrence = detect_recurrence(&mut state, |s| *s = f(s), |s| *s, |s| *s);
+        assert_eq!(brent(3, f), recurrence.cycle);
+        assert_eq!(brent_within(0, step, 3), None);
+        assert_eq!(brent_within(0, step, 10), Some(brent(0, step)));
+    }
+
+    #[test]
+    fn test_recurrence() {
+        let mut state = 0;
+        let recurrence = detect_recurrence(&mut state, |s| *s = step(s), |s| *s, |s| s * 10);
+        assert_eq!(
+            recurrence.cycle,
+            Cycle {
+                start: 3,
+                length: 4
+            }
+        );
+        assert_eq!(recurrence.at(0), 0);
+        assert_eq!(recurrence.at(6), 60);
+        assert_eq!(recurrence.at(7), 30);
+        assert_eq!(recurrence.at(11), 30);
+        // 999_999_997 steps into a 4-step loop is one step past its start
+        assert_eq!(recurrence.at(1_000_000_000), 40);
+        assert_eq!(state_at(0, step, 1_000_000_000), 4);
+        assert_eq!(state_at(0, step, 2), 2);
+    }
+}
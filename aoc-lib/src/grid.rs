@@ -0,0 +1,349 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+// A rectangular grid stored row by row. (x, y) is (column, row), with (0, 0)
+// in the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DenseGrid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> DenseGrid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "every row must be the same width"
+        );
+        Self {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        }
+    }
+
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // Panics past the right edge, like `row` does past the bottom.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "column {} is outside a grid {} wide",
+            x,
+            self.width
+        );
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    // Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> DenseGrid<U> {
+        DenseGrid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: Clone> DenseGrid<T> {
+    // Builds a new grid where the cell at (x, y) comes from `source(x, y)` in
+    // this one. Every transformation is one of these.
+    fn remap<F: Fn(usize, usize) -> (usize, usize)>(
+        &self,
+        width: usize,
+        height: usize,
+        source: F,
+    ) -> Self {
+        Self::from_fn(width, height, |x, y| {
+            let (sx, sy) = source(x, y);
+            self[(sx, sy)].clone()
+        })
+    }
+
+    // Swaps rows and columns, mirroring along the top-left to bottom-right
+    // diagonal.
+    pub fn transpose(&self) -> Self {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    // A quarter turn counter-clockwise: the right column becomes the top row.
+    pub fn rotate_left(&self) -> Self {
+        let w = self.width;
+        self.remap(self.height, self.width, |x, y| (w - 1 - y, x))
+    }
+
+    // A quarter turn clockwise: the left column becomes the top row.
+    pub fn rotate_right(&self) -> Self {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    // Mirrors left to right, so each row is reversed.
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.width;
+        self.remap(self.width, self.height, |x, y| (w - 1 - x, y))
+    }
+
+    // Mirrors top to bottom, so the rows come in reverse order.
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.height;
+        self.remap(self.width, self.height, |x, y| (x, h - 1 - y))
+    }
+
+    // All eight rotations and reflections, starting with the grid as it is and
+    // its three rotations, then the same for its mirror image.
+    pub fn orientations(&self) -> impl Iterator<Item = Self> {
+        let mut all = Vec::with_capacity(8);
+        for mut grid in [self.clone(), self.flip_horizontal()] {
+            for _ in 0..4 {
+                let next = grid.rotate_left();
+                all.push(grid);
+                grid = next;
+            }
+        }
+        all.into_iter()
+    }
+
+    pub fn sub_grid(&self, x: usize, y: usize, width: usize, height: usize) -> Self {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "sub-grid must fit inside the grid"
+        );
+        self.remap(width, height, |i, j| (x + i, y + j))
+    }
+
+    // Lays `tiles` out row by row, the way they'd appear on the page. Every
+    // tile in a row must be as tall as the others, and every row as wide.
+    pub fn stitch(tiles: &[Vec<Self>]) -> Self {
+        let rows = tiles
+            .iter()
+            .flat_map(|tile_row| {
+                let height = tile_row.first().map_or(0, |t| t.height);
+                (0..height).map(move |y| {
+                    tile_row
+                        .iter()
+                        .flat_map(|tile| tile.row(y).iter().cloned())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        Self::from_rows(rows)
+    }
+}
+
+impl<T: PartialEq> DenseGrid<T> {
+    // The rows y with a mirror between y - 1 and y, where reflecting the
+    // smaller side onto the other leaves exactly `differences` cells that
+    // don't match. Transpose the grid to look for vertical mirrors.
+    pub fn mirror_lines(&self, differences: usize) -> impl Iterator<Item = usize> + '_ {
+        (1..self.height).filter(move |&y| {
+            let mismatches: usize = (0..y)
+                .rev()
+                .zip(y..self.height)
+                .map(|(above, below)| {
+                    self.row(above)
+                        .iter()
+                        .zip(self.row(below))
+                        .filter(|(a, b)| a != b)
+                        .count()
+                })
+                .sum();
+            mismatches == differences
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for DenseGrid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is off the grid",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for DenseGrid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is off the grid",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseGridError;
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "grid has a cell that won't parse or rows of different widths"
+        )
+    }
+}
+
+impl std::error::Error for ParseGridError {}
+
+// One character per cell, one line per row.
+impl<T: FromStr> FromStr for DenseGrid<T> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| c.to_string().parse().map_err(|_| ParseGridError))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(ParseGridError);
+        }
+        Ok(Self::from_rows(rows))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DenseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> DenseGrid<char> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_rotations() {
+        let square = grid("ABC\nDEF\nGHI");
+        assert_eq!(square.rotate_left(), grid("CFI\nBEH\nADG"));
+        assert_eq!(square.rotate_right(), grid("GDA\nHEB\nIFC"));
+        assert_eq!(square.rotate_180(), grid("IHG\nFED\nCBA"));
+        assert_eq!(square.rotate_left().rotate_right(), square);
+
+        let wide = grid("ABCD\nEFGH");
+        assert_eq!(wide.rotate_left(), grid("DH\nCG\nBF\nAE"));
+        assert_eq!(wide.rotate_right(), grid("EA\nFB\nGC\nHD"));
+        assert_eq!(wide.rotate_right().width(), 2);
+    }
+
+    #[test]
+    fn test_flips() {
+        let wide = grid("ABCD\nEFGH");
+        assert_eq!(wide.flip_horizontal(), grid("DCBA\nHGFE"));
+        assert_eq!(wide.flip_vertical(), grid("EFGH\nABCD"));
+        assert_eq!(wide.transpose(), grid("AE\nBF\nCG\nDH"));
+        assert_eq!(wide.transpose().transpose(), wide);
+    }
+
+    #[test]
+    fn test_orientations() {
+        let square = grid("AB\nCD");
+        let all: Vec<_> = square.orientations().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], square);
+        for (i, a) in all.iter().enumerate() {
+            assert!(all[i + 1..].iter().all(|b| a != b));
+        }
+        assert!(all.contains(&square.transpose()));
+        assert!(all.contains(&square.flip_vertical()));
+    }
+
+    #[test]
+    fn test_sub_grids() {
+        let g = grid("ABCD\nEFGH\nIJKL");
+        assert_eq!(g.sub_grid(1, 1, 2, 2), grid("FG\nJK"));
+        assert_eq!(g.row(2), &['I', 'J', 'K', 'L']);
+        assert_eq!(g.column(3).collect::<String>(), "DHL");
+        let stitched = DenseGrid::stitch(&[vec![g.sub_grid(0, 0, 2, 3), g.sub_grid(2, 0, 2, 3)]]);
+        assert_eq!(stitched, g);
+        let quarters =
+            DenseGrid::stitch(&[vec![grid("AB"), grid("CD")], vec![grid("EF"), grid("GH")]]);
+        assert_eq!(quarters.to_string(), "ABCD\nEFGH");
+    }
+
+    #[test]
+    #[should_panic(expected = "column 4 is outside a grid 4 wide")]
+    fn test_column_out_of_range() {
+        let _ = grid("ABCD\nEFGH").column(4);
+    }
+
+    #[test]
+    fn test_mirror_lines() {
+        // the second pattern from 2023 day 13
+        let g = grid("#...##..#\n#....#..#\n..##..###\n#####.##.\n#####.##.\n..##..###\n#....#..#");
+        assert_eq!(g.mirror_lines(0).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(g.mirror_lines(1).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(g.transpose().mirror_lines(0).next(), None);
+    }
+}
@@ -0,0 +1,318 @@
+use num::PrimInt;
+
+// Inclusive on both ends, like the ranges in most puzzle inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T = i64> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "interval start must not be after its end");
+        Self { start, end }
+    }
+
+    // None for a length of zero, which no interval can have.
+    pub fn from_len(start: T, len: T) -> Option<Self> {
+        (len > T::zero()).then(|| Self::new(start, start + len - T::one()))
+    }
+
+    pub fn singleton(value: T) -> Self {
+        Self::new(value, value)
+    }
+
+    pub fn len(&self) -> T {
+        self.end - self.start + T::one()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    // Whether every value of `other` is also in `self`.
+    pub fn covers(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.overlaps(other)
+            .then(|| Self::new(self.start.max(other.start), self.end.min(other.end)))
+    }
+
+    // Overlapping or directly adjacent, i.e. the union is a single interval.
+    fn touches(&self, other: &Self) -> bool {
+        self.start <= other.end.saturating_add(T::one())
+            && other.start <= self.end.saturating_add(T::one())
+    }
+}
+
+// Always kept sorted, disjoint and coalesced, so two sets holding the same
+// values compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T = i64> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn coalesce(mut intervals: Vec<Interval<T>>) -> Self {
+        intervals.sort();
+        let mut result: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match result.last_mut() {
+                Some(last) if last.touches(&interval) => last.end = last.end.max(interval.end),
+                _ => result.push(interval),
+            }
+        }
+        Self { intervals: result }
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+        *self = Self::coalesce(intervals);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // The number of values in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::zero(), |acc, interval| acc + interval.len())
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|i| i.end)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.intervals.iter().any(|i| i.contains(value))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::coalesce(self.iter().chain(other.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Vec::new();
+        let (mut a, mut b) = (self.intervals.iter(), other.intervals.iter());
+        let (mut left, mut right) = (a.next(), b.next());
+        while let (Some(l), Some(r)) = (left, right) {
+            if let Some(overlap) = l.intersection(r) {
+                result.push(overlap);
+            }
+            if l.end < r.end {
+                left = a.next();
+            } else {
+                right = b.next();
+            }
+        }
+        Self { intervals: result }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = Vec::new();
+        for interval in &self.intervals {
+            let mut start = Some(interval.start);
+            for cut in other.iter().filter(|c| c.overlaps(interval)) {
+                if let Some(s) = start.filter(|&s| s < cut.start) {
+                    result.push(Interval::new(s, cut.start - T::one()));
+                }
+                start = (cut.end < interval.end).then(|| cut.end + T::one());
+            }
+            if let Some(s) = start.filter(|&s| s <= interval.end) {
+                result.push(Interval::new(s, interval.end));
+            }
+        }
+        Self { intervals: result }
+    }
+}
+
+impl<T: PrimInt> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        Self {
+            intervals: vec![interval],
+        }
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self::coalesce(iter.into_iter().collect())
+    }
+}
+
+// Sends every value in `source` to the same position relative to
+// `destination_start`, i.e. `source.start` maps to `destination_start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RangeRule<T = i64> {
+    pub source: Interval<T>,
+    pub destination_start: T,
+}
+
+impl<T: PrimInt> RangeRule<T> {
+    // None if the rule covers nothing.
+    pub fn new(destination_start: T, source_start: T, len: T) -> Option<Self> {
+        Some(Self {
+            source: Interval::from_len(source_start, len)?,
+            destination_start,
+        })
+    }
+
+    fn shift(&self, value: T) -> T {
+        value - self.source.start + self.destination_start
+    }
+
+    fn shift_interval(&self, interval: &Interval<T>) -> Interval<T> {
+        Interval::new(self.shift(interval.start), self.shift(interval.end))
+    }
+}
+
+// A piecewise mapping made of rules with non-overlapping sources. Anything not
+// covered by a rule maps to itself.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeMap<T = i64> {
+    rules: Vec<RangeRule<T>>,
+}
+
+impl<T: PrimInt> RangeMap<T> {
+    pub fn new(rules: Vec<RangeRule<T>>) -> Self {
+        Self { rules }
+    }
+
+    pub fn get(&self, value: T) -> T {
+        self.rules
+            .iter()
+            .find(|r| r.source.contains(value))
+            .map_or(value, |r| r.shift(value))
+    }
+
+    pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut unmapped = set.clone();
+        let mut mapped = Vec::new();
+        for rule in &self.rules {
+            let source = IntervalSet::from(rule.source);
+            mapped.extend(
+                unmapped
+                    .intersection(&source)
+                    .iter()
+                    .map(|i| rule.shift_interval(i)),
+            );
+            unmapped = unmapped.difference(&source);
+        }
+        mapped.extend(unmapped.intervals);
+        IntervalSet::coalesce(mapped)
+    }
+}
+
+impl<T: PrimInt> FromIterator<RangeRule<T>> for RangeMap<T> {
+    fn from_iter<I: IntoIterator<Item = RangeRule<T>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+// Pushes a whole set of values through each map in turn.
+pub fn map_through<'a, T: PrimInt + 'a>(
+    maps: impl IntoIterator<Item = &'a RangeMap<T>>,
+    set: &IntervalSet<T>,
+) -> IntervalSet<T> {
+    maps.into_iter()
+        .fold(set.clone(), |acc, map| map.map_set(&acc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(start, end)| Interval::new(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn test_intervals() {
+        assert_eq!(Interval::from_len(5, 3), Some(Interval::new(5, 7)));
+        assert_eq!(Interval::from_len(5, 0), None);
+        assert_eq!(Interval::from_len(5u8, 0), None);
+        assert!(Interval::new(0, 10).covers(&Interval::new(3, 10)));
+        assert!(!Interval::new(0, 10).covers(&Interval::new(3, 11)));
+    }
+
+    #[test]
+    fn test_coalescing() {
+        let s = set(&[(5, 7), (1, 3), (4, 4), (10, 12), (11, 20)]);
+        assert_eq!(
+            s.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::new(1, 7), Interval::new(10, 20)]
+        );
+        assert_eq!(s.len(), 18);
+        assert_eq!((s.min(), s.max()), (Some(1), Some(20)));
+        assert!(s.contains(15) && !s.contains(8));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (40, 41)]);
+        assert_eq!(a.union(&b), set(&[(0, 30), (40, 41)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25)]));
+        assert_eq!(a.difference(&b), set(&[(0, 4), (26, 30)]));
+        assert_eq!(b.difference(&a), set(&[(11, 19), (40, 41)]));
+        assert_eq!(a.difference(&a), IntervalSet::new());
+        assert_eq!(
+            set(&[(0, 10)]).difference(&set(&[(3, 4), (6, 6)])),
+            set(&[(0, 2), (5, 5), (7, 10)])
+        );
+    }
+
+    #[test]
+    fn test_range_map() {
+        // seed-to-soil from the 2023 day 5 example
+        let map: RangeMap = [RangeRule::new(50, 98, 2), RangeRule::new(52, 50, 48)]
+            .into_iter()
+            .collect::<Option<_>>()
+            .unwrap();
+        assert_eq!(map.get(79), 81);
+        assert_eq!(map.get(99), 51);
+        assert_eq!(map.get(10), 10);
+        let seeds = set(&[(79, 92), (55, 67)]);
+        assert_eq!(map.map_set(&seeds), set(&[(57, 69), (81, 94)]));
+        assert_eq!(map.map_set(&set(&[(40, 99)])), set(&[(40, 99)]));
+
+        let shift_back = RangeMap::new(RangeRule::new(0, 50, 50).into_iter().collect());
+        assert_eq!(RangeRule::new(0, 50, 0), None);
+        assert_eq!(
+            map_through([&map, &shift_back], &set(&[(96, 99)])),
+            set(&[(0, 1), (48, 49)])
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod automaton;
+pub mod cycles;
+pub mod grid;
+pub mod intervals;
+pub mod number_theory;
+pub mod ocr;
+pub mod search;
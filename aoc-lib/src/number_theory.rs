@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a
+}
+
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+pub fn gcd_all<I: IntoIterator<Item = i64>>(values: I) -> i64 {
+    values.into_iter().fold(0, gcd)
+}
+
+pub fn lcm_all<I: IntoIterator<Item = i64>>(values: I) -> i64 {
+    values.into_iter().fold(1, lcm)
+}
+
+// Returns (g, x, y) such that a * x + b * y = g = gcd(a, b).
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    (g == 1).then(|| x.rem_euclid(modulus))
+}
+
+fn mul_mod(a: i64, b: i64, modulus: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(modulus as i128) as i64
+}
+
+pub fn mod_pow(base: i64, exponent: i64, modulus: i64) -> i64 {
+    assert!(exponent >= 0, "negative exponents need mod_inverse first");
+    let mut result = 1 % modulus;
+    let mut base = base.rem_euclid(modulus);
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+// Solves x = residue (mod modulus) for every pair at once. The moduli don't
+// have to be coprime; None means the congruences contradict each other or
+// the combined modulus doesn't fit in an i64. Returns the smallest
+// non-negative x along with the combined modulus.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0i64, 1i64), |(r1, m1), &(r2, m2)| {
+            let (g, p, _) = extended_gcd(m1, m2);
+            let diff = r2 - r1;
+            if diff % g != 0 {
+                return None;
+            }
+            let m2_g = m2 / g;
+            let combined = m1 as i128 * m2_g as i128;
+            if combined > i64::MAX as i128 {
+                return None;
+            }
+            // m1 * p = g (mod m2), so stepping by m1 * (diff / g) * p closes the gap.
+            let k = ((diff / g) as i128 * p as i128).rem_euclid(m2_g as i128);
+            let x = (r1 as i128 + m1 as i128 * k).rem_euclid(combined);
+            Some((x as i64, combined as i64))
+        })
+}
+
+// Baby-step giant-step: the smallest x >= 0 with base^x = target (mod modulus).
+// Requires base and modulus to be coprime.
+pub fn discrete_log(base: i64, target: i64, modulus: i64) -> Option<i64> {
+    let target = target.rem_euclid(modulus);
+    let step = (modulus as f64).sqrt().ceil() as i64;
+    let mut baby_steps = HashMap::new();
+    let mut value = 1 % modulus;
+    for j in 0..step {
+        baby_steps.entry(value).or_insert(j);
+        value = mul_mod(value, base, modulus);
+    }
+    let giant = mod_pow(mod_inverse(base, modulus)?, step, modulus);
+    let mut gamma = target;
+    for i in 0..step {
+        if let Some(j) = baby_steps.get(&gamma) {
+            return Some(i * step + j);
+        }
+        gamma = mul_mod(gamma, giant, modulus);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(gcd_all(vec![12, 18, 30]), 6);
+        assert_eq!(lcm_all(vec![2, 3, 4, 5]), 60);
+        assert_eq!(lcm_all(Vec::new()), 1);
+    }
+
+    #[test]
+    fn test_extended_gcd_and_inverse() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(7, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(0, 17), (-2, 13), (-3, 19)]), Some((3417, 4199)));
+        // not coprime, but consistent
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        let big = (1 << 62) - 57;
+        assert_eq!(crt(&[(1, big), (2, 7)]), None);
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        assert_eq!(discrete_log(2, 0, 11), None);
+    }
+}
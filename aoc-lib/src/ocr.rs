@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+// The block letters some puzzles draw their answers in. Letters are 6 or 10
+// pixels tall and sit one blank column apart; only the letters that have
+// turned up in puzzles so far are known.
+const FONT_6: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const FONT_10: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+type Pixels = Vec<Vec<bool>>;
+
+// '#' is lit and anything else is dark. Rows can be ragged.
+fn pixels(screen: &str) -> Pixels {
+    let rows: Vec<Vec<bool>> = screen
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#').collect())
+        .collect();
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut rows: Vec<Vec<bool>> = rows
+        .into_iter()
+        .map(|mut row| {
+            row.resize(width, false);
+            row
+        })
+        .collect();
+    while rows.first().is_some_and(|r| !r.contains(&true)) {
+        rows.remove(0);
+    }
+    while rows.last().is_some_and(|r| !r.contains(&true)) {
+        rows.pop();
+    }
+    rows
+}
+
+// Cuts the screen into letters wherever there's a blank column.
+fn glyphs(pixels: &Pixels) -> Vec<Pixels> {
+    let width = pixels.first().map_or(0, |r| r.len());
+    let lit = |x: usize| pixels.iter().any(|row| row[x]);
+    let mut glyphs = Vec::new();
+    let mut x = 0;
+    while x < width {
+        if !lit(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < width && lit(x) {
+            x += 1;
+        }
+        glyphs.push(pixels.iter().map(|row| row[start..x].to_vec()).collect());
+    }
+    glyphs
+}
+
+fn letter(font: &[(char, &str)], glyph: &Pixels) -> Option<char> {
+    font.iter()
+        .find(|(_, drawing)| glyphs(&pixels(drawing)).first() == Some(glyph))
+        .map(|&(c, _)| c)
+}
+
+// Reads the letters drawn on a screen, or None if there's anything on it
+// that isn't a known letter.
+pub fn read_screen(screen: &str) -> Option<String> {
+    let pixels = pixels(screen);
+    let font = match pixels.len() {
+        6 => FONT_6,
+        10 => FONT_10,
+        _ => return None,
+    };
+    glyphs(&pixels)
+        .iter()
+        .map(|glyph| letter(font, glyph))
+        .collect()
+}
+
+// Draws lit points as a screen, with the top left point at the corner.
+pub fn render_points<I: IntoIterator<Item = (i64, i64)>>(points: I) -> String {
+    let points: HashSet<(i64, i64)> = points.into_iter().collect();
+    let (min_x, max_x) = (
+        points.iter().map(|p| p.0).min().unwrap_or(0),
+        points.iter().map(|p| p.0).max().unwrap_or(-1),
+    );
+    let (min_y, max_y) = (
+        points.iter().map(|p| p.1).min().unwrap_or(0),
+        points.iter().map(|p| p.1).max().unwrap_or(-1),
+    );
+    (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| if points.contains(&(x, y)) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn read_points<I: IntoIterator<Item = (i64, i64)>>(points: I) -> Option<String> {
+    read_screen(&render_points(points))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_letters() {
+        // 2022 day 10, with the CRT's trailing blank column
+        let crt = "\
+####.####.####.###..###...##..#..#.#....
+#.......#.#....#..#.#..#.#..#.#.#..#....
+###....#..###..#..#.#..#.#..#.##...#....
+#.....#...#....###..###..####.#.#..#....
+#....#....#....#....#.#..#..#.#.#..#....
+####.####.#....#....#..#.#..#.#..#.####.";
+        assert_eq!(read_screen(crt), Some("EZFPRAKL".to_string()));
+
+        // 2021 day 13, where the paper is only as wide as its dots
+        let paper = "\
+###..#....#..#...##.###..###...##...##.
+#..#.#....#.#.....#.#..#.#..#.#..#.#..#
+###..#....##......#.#..#.###..#..#.#...
+#..#.#....#.#.....#.###..#..#.####.#.##
+#..#.#....#.#..#..#.#.#..#..#.#..#.#..#
+###..####.#..#..##..#..#.###..#..#..###";
+        assert_eq!(read_screen(paper), Some("BLKJRBAG".to_string()));
+    }
+
+    #[test]
+    fn test_every_letter() {
+        for font in [FONT_6, FONT_10] {
+            let screen = (0..font[0].1.lines().count())
+                .map(|y| {
+                    font.iter()
+                        .map(|(_, drawing)| drawing.lines().nth(y).unwrap())
+                        .collect::<Vec<_>>()
+                        .join(".")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let expected: String = font.iter().map(|&(c, _)| c).collect();
+            assert_eq!(read_screen(&screen), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_points() {
+        // an H drawn somewhere off in the distance
+        let h = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (1, 2),
+            (2, 2),
+        ]
+        .iter()
+        .chain(&[(3, 0), (3, 1), (3, 2), (3, 3), (3, 4), (3, 5)])
+        .map(|&(x, y)| (x + 100, y - 50));
+        assert_eq!(read_points(h), Some("H".to_string()));
+        assert_eq!(render_points(vec![(1, 1), (2, 2)]), "#.\n.#");
+    }
+
+    #[test]
+    fn test_unreadable() {
+        assert_eq!(read_screen("#####\n#...#\n#...#\n#...#\n#####"), None);
+        assert_eq!(read_screen("###.\n#..#\n#..#\n###.\n#..#\n#..."), None);
+        assert_eq!(read_screen(""), None);
+    }
+}
@@ -0,0 +1,431 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use num::Zero;
+
+// An implicit graph: nodes are only ever discovered by asking for the edges
+// out of a node we already have. Any `Fn(&N) -> Vec<(N, C)>` is one.
+pub trait Successors<N> {
+    type Cost;
+
+    fn successors(&self, node: &N) -> Vec<(N, Self::Cost)>;
+}
+
+impl<N, C, F> Successors<N> for F
+where
+    F: Fn(&N) -> Vec<(N, C)>,
+{
+    type Cost = C;
+
+    fn successors(&self, node: &N) -> Vec<(N, C)> {
+        self(node)
+    }
+}
+
+// A route through the graph, start and goal included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub cost: C,
+    pub nodes: Vec<N>,
+}
+
+// Every node reached so far, with the node it was reached from and the best
+// cost found to it. Nodes are referred to by their position in `nodes`.
+struct Explored<N, C> {
+    nodes: Vec<(N, Option<usize>, C)>,
+    index: HashMap<N, usize>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Explored<N, C> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, node: N, parent: Option<usize>, cost: C) -> usize {
+        let i = self.nodes.len();
+        self.index.insert(node.clone(), i);
+        self.nodes.push((node, parent, cost));
+        i
+    }
+
+    fn path(&self, mut i: usize) -> Path<N, C> {
+        let cost = self.nodes[i].2;
+        let mut nodes = vec![self.nodes[i].0.clone()];
+        while let Some(parent) = self.nodes[i].1 {
+            nodes.push(self.nodes[parent].0.clone());
+            i = parent;
+        }
+        nodes.reverse();
+        Path { cost, nodes }
+    }
+}
+
+// The fewest edges from any of `starts` to a node passing `is_goal`. Edge
+// costs are ignored.
+pub fn bfs<N, G, F>(
+    graph: &G,
+    starts: impl IntoIterator<Item = N>,
+    is_goal: F,
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+    F: Fn(&N) -> bool,
+{
+    let mut explored = Explored::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !explored.index.contains_key(&start) {
+            queue.push_back(explored.insert(start, None, 0));
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        if is_goal(&explored.nodes[i].0) {
+            return Some(explored.path(i));
+        }
+        let depth = explored.nodes[i].2 + 1;
+        for (next, _) in graph.successors(&explored.nodes[i].0) {
+            if !explored.index.contains_key(&next) {
+                queue.push_back(explored.insert(next, Some(i), depth));
+            }
+        }
+    }
+    None
+}
+
+// The number of edges to every node reachable from `starts`.
+pub fn bfs_distances<N, G>(graph: &G, starts: impl IntoIterator<Item = N>) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        let depth = distances[&node] + 1;
+        for (next, _) in graph.successors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), depth);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+// Searches outwards from both ends at once, a whole level at a time from
+// whichever side has the smaller frontier. `backward` has to give the edges
+// into a node; for undirected graphs that's the same as `forward`.
+pub fn bidirectional_bfs<N, G, B>(
+    forward: &G,
+    backward: &B,
+    start: N,
+    goal: N,
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+    B: Successors<N>,
+{
+    if start == goal {
+        return Some(Path {
+            cost: 0,
+            nodes: vec![start],
+        });
+    }
+    let mut from_start: HashMap<N, (Option<N>, usize)> = HashMap::new();
+    let mut from_goal: HashMap<N, (Option<N>, usize)> = HashMap::new();
+    from_start.insert(start.clone(), (None, 0));
+    from_goal.insert(goal.clone(), (None, 0));
+    let mut start_frontier = vec![start];
+    let mut goal_frontier = vec![goal];
+    while !start_frontier.is_empty() && !goal_frontier.is_empty() {
+        let forwards = start_frontier.len() <= goal_frontier.len();
+        let (frontier, seen, other) = if forwards {
+            (&mut start_frontier, &mut from_start, &from_goal)
+        } else {
+            (&mut goal_frontier, &mut from_goal, &from_start)
+        };
+        let mut meeting: Option<(N, usize)> = None;
+        let mut next_frontier = Vec::new();
+        for node in frontier.drain(..) {
+            let depth = seen[&node].1 + 1;
+            let successors = if forwards {
+                forward
+                    .successors(&node)
+                    .into_iter()
+                    .map(|(n, _)| n)
+                    .collect::<Vec<_>>()
+            } else {
+                backward
+                    .successors(&node)
+                    .into_iter()
+                    .map(|(n, _)| n)
+                    .collect()
+            };
+            for next in successors {
+                if seen.contains_key(&next) {
+                    continue;
+                }
+                seen.insert(next.clone(), (Some(node.clone()), depth));
+                if let Some(&(_, other_depth)) = other.get(&next) {
+                    let total = depth + other_depth;
+                    if meeting.as_ref().is_none_or(|(_, best)| total < *best) {
+                        meeting = Some((next.clone(), total));
+                    }
+                }
+                next_frontier.push(next);
+            }
+        }
+        *frontier = next_frontier;
+        if let Some((middle, cost)) = meeting {
+            let mut nodes = walk_back(&from_start, middle.clone());
+            nodes.reverse();
+            nodes.extend(walk_back(&from_goal, middle).into_iter().skip(1));
+            return Some(Path { cost, nodes });
+        }
+    }
+    None
+}
+
+fn walk_back<N: Clone + Eq + Hash>(seen: &HashMap<N, (Option<N>, usize)>, node: N) -> Vec<N> {
+    let mut nodes = vec![node];
+    while let Some((Some(parent), _)) = seen.get(nodes.last().unwrap()) {
+        nodes.push(parent.clone());
+    }
+    nodes
+}
+
+// The cheapest path from any of `starts` to a node passing `is_goal`.
+// `heuristic` must never overestimate the remaining cost, and shouldn't drop
+// by more than an edge costs, or the path found may not be the cheapest.
+pub fn astar<N, G, H, F>(
+    graph: &G,
+    starts: impl IntoIterator<Item = N>,
+    heuristic: H,
+    is_goal: F,
+) -> Option<Path<N, G::Cost>>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+    G::Cost: Copy + Ord + Zero,
+    H: Fn(&N) -> G::Cost,
+    F: Fn(&N) -> bool,
+{
+    let mut explored = Explored::new();
+    let mut frontier = BinaryHeap::new();
+    for start in starts {
+        if !explored.index.contains_key(&start) {
+            let estimate = heuristic(&start);
+            let i = explored.insert(start, None, G::Cost::zero());
+            frontier.push(Reverse((estimate, G::Cost::zero(), i)));
+        }
+    }
+    while let Some(Reverse((_, cost, i))) = frontier.pop() {
+        if cost > explored.nodes[i].2 {
+            continue;
+        }
+        if is_goal(&explored.nodes[i].0) {
+            return Some(explored.path(i));
+        }
+        for (next, step) in graph.successors(&explored.nodes[i].0) {
+            let next_cost = cost + step;
+            let j = match explored.index.get(&next) {
+                Some(&j) if explored.nodes[j].2 <= next_cost => continue,
+                Some(&j) => {
+                    explored.nodes[j].1 = Some(i);
+                    explored.nodes[j].2 = next_cost;
+                    j
+                }
+                None => explored.insert(next, Some(i), next_cost),
+            };
+            let estimate = next_cost + heuristic(&explored.nodes[j].0);
+            frontier.push(Reverse((estimate, next_cost, j)));
+        }
+    }
+    None
+}
+
+pub fn dijkstra<N, G, F>(
+    graph: &G,
+    starts: impl IntoIterator<Item = N>,
+    is_goal: F,
+) -> Option<Path<N, G::Cost>>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+    G::Cost: Copy + Ord + Zero,
+    F: Fn(&N) -> bool,
+{
+    astar(graph, starts, |_| G::Cost::zero(), is_goal)
+}
+
+// The cost of the cheapest path to the first goal reached, and how many
+// distinct paths share that cost. Edge costs must be positive.
+pub fn count_shortest_paths<N, G, F>(
+    graph: &G,
+    starts: impl IntoIterator<Item = N>,
+    is_goal: F,
+) -> Option<(G::Cost, usize)>
+where
+    N: Clone + Eq + Hash,
+    G: Successors<N>,
+    G::Cost: Copy + Ord + Zero,
+    F: Fn(&N) -> bool,
+{
+    // (node, best cost, number of paths at that cost)
+    let mut nodes: Vec<(N, G::Cost, usize)> = Vec::new();
+    let mut index: HashMap<N, usize> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    for start in starts {
+        if !index.contains_key(&start) {
+            index.insert(start.clone(), nodes.len());
+            frontier.push(Reverse((G::Cost::zero(), nodes.len())));
+            nodes.push((start, G::Cost::zero(), 1));
+        }
+    }
+    while let Some(Reverse((cost, i))) = frontier.pop() {
+        if cost > nodes[i].1 {
+            continue;
+        }
+        let count = nodes[i].2;
+        if is_goal(&nodes[i].0) {
+            return Some((cost, count));
+        }
+        for (next, step) in graph.successors(&nodes[i].0) {
+            let next_cost = cost + step;
+            match index.get(&next) {
+                Some(&j) if nodes[j].1 < next_cost => {}
+                Some(&j) if nodes[j].1 == next_cost => nodes[j].2 += count,
+                Some(&j) => {
+                    nodes[j].1 = next_cost;
+                    nodes[j].2 = count;
+                    frontier.push(Reverse((next_cost, j)));
+                }
+                None => {
+                    index.insert(next.clone(), nodes.len());
+                    frontier.push(Reverse((next_cost, nodes.len())));
+                    nodes.push((next, next_cost, count));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 5x5 grid with a wall down the middle that has a gap at the bottom.
+    fn maze(&(x, y): &(i32, i32)) -> Vec<((i32, i32), u32)> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y))
+            .filter(|&(x, y)| x != 2 || y == 4)
+            .map(|p| (p, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(&maze, [(0, 0)], |&p| p == (4, 0)).unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.nodes.first(), Some(&(0, 0)));
+        assert!(path.nodes.contains(&(2, 4)));
+
+        // starting on either side of the wall
+        let path = bfs(&maze, [(0, 0), (3, 0)], |&p| p == (4, 0)).unwrap();
+        assert_eq!(path.nodes, vec![(3, 0), (4, 0)]);
+        assert_eq!(bfs(&maze, [(0, 0)], |&p| p == (2, 0)), None);
+
+        let distances = bfs_distances(&maze, [(0, 0)]);
+        assert_eq!(distances.len(), 21);
+        assert_eq!(distances[&(4, 4)], 8);
+    }
+
+    #[test]
+    fn test_bidirectional_bfs() {
+        let path = bidirectional_bfs(&maze, &maze, (0, 0), (4, 0)).unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.nodes.first(), Some(&(0, 0)));
+        assert_eq!(path.nodes.last(), Some(&(4, 0)));
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|w| maze(&w[0]).iter().any(|(n, _)| n == &w[1])));
+        assert_eq!(bidirectional_bfs(&maze, &maze, (0, 0), (9, 9)), None);
+        assert_eq!(
+            bidirectional_bfs(&maze, &maze, (1, 1), (1, 1))
+                .unwrap()
+                .cost,
+            0
+        );
+    }
+
+    #[test]
+    fn test_weighted() {
+        // going right is expensive, so it's cheaper to go down and around
+        let graph = |&(x, y): &(i32, i32)| -> Vec<((i32, i32), u32)> {
+            let mut next = Vec::new();
+            if x < 3 {
+                next.push(((x + 1, y), if y == 0 { 10 } else { 1 }));
+            }
+            if y < 1 {
+                next.push(((x, y + 1), 1));
+            }
+            if y > 0 {
+                next.push(((x, y - 1), 1));
+            }
+            next
+        };
+        let path = dijkstra(&graph, [(0, 0)], |&p| p == (3, 0)).unwrap();
+        assert_eq!(path.cost, 5);
+        assert_eq!(
+            path.nodes,
+            vec![(0, 0), (0, 1), (1, 1), (2, 1), (3, 1), (3, 0)]
+        );
+
+        let manhattan = |&(x, y): &(i32, i32)| ((3 - x).abs() + y.abs()) as u32;
+        let found = astar(&graph, [(0, 0)], manhattan, |&p| p == (3, 0)).unwrap();
+        assert_eq!(found, path);
+    }
+
+    #[test]
+    fn test_count_shortest_paths() {
+        // lattice paths across an open 3x3 grid
+        let open = |&(x, y): &(i32, i32)| -> Vec<((i32, i32), u32)> {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .map(|(dx, dy)| ((x + dx, y + dy), 1))
+                .filter(|((x, y), _)| (0..3).contains(x) && (0..3).contains(y))
+                .collect()
+        };
+        assert_eq!(
+            count_shortest_paths(&open, [(0, 0)], |&p| p == (2, 2)),
+            Some((4, 6))
+        );
+        // 5 ways down to the gap and 5 ways back up
+        assert_eq!(
+            count_shortest_paths(&maze, [(0, 0)], |&p| p == (4, 0)),
+            Some((12, 25))
+        );
+        assert_eq!(
+            count_shortest_paths(&maze, [(0, 0)], |&p| p == (2, 0)),
+            None
+        );
+    }
+}